@@ -1,5 +1,5 @@
 use alloy::{
-    primitives::{utils::parse_units, Address, U256},
+    primitives::{b256, utils::parse_units, Address, Bytes, B256, U256},
     providers::{ext::DebugApi, Provider, RootProvider},
     pubsub::PubSubFrontend,
     rpc::types::{
@@ -10,39 +10,52 @@ use alloy::{
                 GethDebugTracingOptions, GethTrace,
             },
         },
-        Block, Transaction,
+        Block, Transaction, TransactionReceipt,
     },
 };
 use bb8::PooledConnection;
 use bb8_postgres::PostgresConnectionManager;
 use eyre::Result;
+use futures::future::try_join_all;
 use nimiq_database::{
     mdbx::{MdbxDatabase, MdbxWriteTransaction},
     traits::{Database, WriteTransaction},
 };
-use std::collections::BTreeMap;
+use rust_decimal::Decimal;
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    sync::{atomic::AtomicBool, Arc},
+};
 use tokio_postgres::{Client as PostgresClient, NoTls};
 
 use crate::{
     cache::Cache,
-    constant::POS_BLOCK_NUMBER,
+    error::ScanError,
+    policy::PolicyKind,
     primitives::{AddressKey, Score},
-    score_db::ScoreDb,
+    score_db::{PriceSource, ScoreDb},
+    spec::ChainSpec,
 };
 
+/// `keccak256("Transfer(address,address,uint256)")`: the ERC-20/ERC-721 log topic0.
+const TRANSFER_TOPIC: B256 =
+    b256!("ddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef");
+
 pub struct Blockchain {
     score_db: ScoreDb,
     cache: Cache,
     db: MdbxDatabase,
+    spec: ChainSpec,
 }
 
 impl Blockchain {
     /// Loads existing state.
-    pub fn load(db: MdbxDatabase) -> Self {
+    pub fn load(db: MdbxDatabase, spec: ChainSpec, policy: PolicyKind, cache_capacity: usize) -> Self {
         Self {
-            score_db: ScoreDb::new(db.clone()),
-            cache: Cache::new(),
+            score_db: ScoreDb::new(db.clone(), policy),
+            cache: Cache::new(cache_capacity),
             db,
+            spec,
         }
     }
 
@@ -56,15 +69,19 @@ impl Blockchain {
     pub async fn init_new(
         db: MdbxDatabase,
         provider: &RootProvider<PubSubFrontend>,
+        spec: ChainSpec,
+        policy: PolicyKind,
+        cache_capacity: usize,
         block_number: u64,
     ) -> Result<Self> {
-        let blockchain = Blockchain::load(db);
+        let blockchain = Blockchain::load(db, spec, policy, cache_capacity);
         let mut txn = blockchain.db.write_transaction();
         let cache = &blockchain.cache;
         blockchain.score_db.clear(&mut txn);
+        blockchain.score_db.write_meta(&mut txn);
         blockchain
             .score_db
-            .init_tc(cache, provider, block_number)
+            .init_dirty_sources(cache, provider, &blockchain.spec.dirty_sources, block_number)
             .await?;
         blockchain
             .score_db
@@ -75,19 +92,22 @@ impl Blockchain {
     }
 
     /// Atomically record all transactions in a block.
+    ///
+    /// Returns `true` when a stop was requested mid-run: this block is still
+    /// committed, but the caller should not begin another one.
     pub async fn record_block(
         &self,
         block: Block,
         provider: &RootProvider<PubSubFrontend>,
-    ) -> Result<()> {
+    ) -> Result<bool> {
         let mut txn = self.db.write_transaction();
         let cache = &self.cache;
 
-        let block_number = block.header.number.expect("Block should have a number");
+        let block_number = block.header.number.ok_or(ScanError::MissingBlockNumber)?;
         let block_transactions = block
             .transactions
             .as_transactions()
-            .expect("Cannot get the block transactions!");
+            .ok_or(ScanError::MissingBlockTransactions { block: block_number })?;
 
         // Deal with uncleanliness state change using Geth Debug trace results
         let geth_trace_options = GethDebugTracingOptions::default().with_tracer(
@@ -96,28 +116,186 @@ impl Blockchain {
         let geth_trace_results = provider
             .debug_trace_block_by_number(block_number.into(), geth_trace_options)
             .await?;
+
+        // Pre-pass: walk the whole set of traces first to collect every
+        // independent RPC query the per-frame processing will need, then issue
+        // them all concurrently instead of awaiting each one serially inside
+        // the DFS.
+        let mut tx_hashes: Vec<B256> = Vec::new();
+        let mut selfdestruct_addrs: BTreeSet<Address> = BTreeSet::new();
+        for geth_trace_result in geth_trace_results.iter() {
+            if let TraceResult::Success { result, tx_hash } = geth_trace_result {
+                if let Some(tx_hash) = tx_hash {
+                    tx_hashes.push(*tx_hash);
+                }
+                if let Ok(frame) = result.clone().try_into_call_frame() {
+                    Self::collect_selfdestruct_addresses(&frame, &mut selfdestruct_addrs);
+                }
+            }
+        }
+        let uncle_count = block.uncles.len() as u64;
+
+        let (receipts, codes, uncles) = futures::future::try_join3(
+            try_join_all(
+                tx_hashes
+                    .iter()
+                    .map(|tx_hash| provider.get_transaction_receipt(*tx_hash)),
+            ),
+            try_join_all(selfdestruct_addrs.iter().map(|address| {
+                provider.get_code_at(*address).block_id(block_number.into())
+            })),
+            try_join_all(
+                (0..uncle_count).map(|idx| provider.get_uncle(block_number.into(), idx)),
+            ),
+        )
+        .await?;
+
+        // Index the resolved results so `process_frame`/`record_reward` can read
+        // them from memory instead of awaiting one query at a time.
+        let mut receipt_map: HashMap<B256, TransactionReceipt> = HashMap::new();
+        for (tx_hash, receipt) in tx_hashes.iter().zip(receipts.into_iter()) {
+            let receipt = receipt.ok_or_else(|| ScanError::ReceiptUnavailable {
+                tx: tx_hash.to_string(),
+            })?;
+            receipt_map.insert(*tx_hash, receipt);
+        }
+        let code_map: HashMap<Address, Bytes> =
+            selfdestruct_addrs.into_iter().zip(codes.into_iter()).collect();
+
         for (transaction, geth_trace_result) in
             block_transactions.iter().zip(geth_trace_results.iter())
         {
-            self.record_transaction(
-                &mut txn,
-                cache,
-                &block,
-                transaction,
-                geth_trace_result,
-                provider,
-            )
-            .await?;
+            // A single corrupt trace must not abort a multi-million-block run:
+            // log it with its block/tx context and move on to the next
+            // transaction. Every other failure still propagates.
+            if let Err(e) = self
+                .record_transaction(
+                    &mut txn,
+                    cache,
+                    &block,
+                    transaction,
+                    geth_trace_result,
+                    provider,
+                    &receipt_map,
+                    &code_map,
+                )
+                .await
+            {
+                match e.downcast_ref::<ScanError>() {
+                    Some(ScanError::TraceCorrupt { block, tx }) => {
+                        eprintln!("Trace Failed in block {block} for tx {tx}; skipping");
+                        continue;
+                    }
+                    _ => return Err(e),
+                }
+            }
         }
 
-        self.record_reward(&mut txn, cache, block, provider).await?;
+        self.record_reward(&mut txn, cache, block, provider, uncles)
+            .await?;
 
-        self.score_db
+        let aborted = self
+            .score_db
             .flush_cache(&mut txn, provider, cache, block_number)
             .await?;
 
         txn.commit();
 
+        Ok(aborted)
+    }
+
+    /// Persist a checkpoint at `block_number`, replacing the previous one.
+    /// Everything the cache held for this block has already been drained into
+    /// the score tables, so the checkpoint only needs to mark the point up to
+    /// which that work is known complete.
+    pub fn checkpoint(&self, block_number: u64) -> Result<()> {
+        let mut txn = self.db.write_transaction();
+        self.score_db.write_checkpoint(&mut txn, block_number);
+        txn.commit();
+        Ok(())
+    }
+
+    /// Height of the most recent complete checkpoint, if one was written.
+    pub fn get_checkpoint(&self) -> Option<u64> {
+        let txn = self.db.read_transaction();
+        self.score_db.get_checkpoint(&txn)
+    }
+
+    /// Prepare to resume after a restart by rewinding to the most recent
+    /// complete checkpoint: any block committed past it is undone (it may be
+    /// part of a range that was only partially applied before the crash).
+    /// Returns the block number to resume scanning from. The self-destruct set
+    /// needs no restore — it is drained per transaction, so it is always empty
+    /// at a committed boundary, and a reorg of a self-destruct reverts through
+    /// [`ScoreDb::undo_block`]'s snapshot rollback like any other change.
+    pub fn resume_from_checkpoint(&self) -> Result<u64> {
+        let checkpoint = self.get_checkpoint();
+        let floor = checkpoint.unwrap_or(self.spec.ini_block_number - 1);
+        if let Some(last_recorded) = self.get_last_block_number() {
+            for block_number in ((floor + 1)..=last_recorded).rev() {
+                self.undo_block(block_number)?;
+            }
+        }
+        Ok(floor + 1)
+    }
+
+    /// Undo a block that was orphaned by a reorg, reverting every score it
+    /// contributed. Delegates to [`ScoreDb::undo_block`], which uses the
+    /// per-block provenance to restore each touched address to its prior value.
+    pub fn undo_block(&self, block_number: u64) -> Result<()> {
+        let mut txn = self.db.write_transaction();
+        self.score_db.undo_block(&mut txn, block_number)?;
+        txn.commit();
+        Ok(())
+    }
+
+    /// A handle to the scan's abort flag; set it to ask an in-flight scan or
+    /// backfill to commit its current block and stop cleanly.
+    pub fn abort_handle(&self) -> Arc<AtomicBool> {
+        self.score_db.abort_handle()
+    }
+
+    /// Checks that this database's recorded format version and policy are
+    /// compatible with the current run before resuming into it.
+    pub fn verify_meta(&self) -> Result<()> {
+        let txn = self.db.read_transaction();
+        self.score_db.verify_meta(&txn)?;
+        Ok(())
+    }
+
+    /// Extend the scored history backward to cover `[to_block, from_block]`
+    /// (with `from_block >= to_block`), committing one block at a time in
+    /// descending order so an abort never loses progress.
+    ///
+    /// Blocks already inside the covered range are skipped, so a backfill that
+    /// was aborted partway resumes just below the lowest block it had reached
+    /// rather than restarting from `from_block`.
+    pub async fn backfill_blocks(
+        &self,
+        provider: &RootProvider<PubSubFrontend>,
+        from_block: u64,
+        to_block: u64,
+    ) -> Result<()> {
+        // Resume below whatever the envelope already covers so we never redo a
+        // block, and never climb above the forward-built range.
+        let start = {
+            let txn = self.db.read_transaction();
+            match self.score_db.get_scan_progress(&txn) {
+                Some(progress) => from_block.min(progress.lowest_block.saturating_sub(1)),
+                None => from_block,
+            }
+        };
+        for block_number in (to_block..=start).rev() {
+            let mut txn = self.db.write_transaction();
+            let aborted = self
+                .score_db
+                .backfill_block(&mut txn, provider, block_number)
+                .await?;
+            txn.commit();
+            if aborted {
+                break;
+            }
+        }
         Ok(())
     }
 
@@ -130,8 +308,10 @@ impl Blockchain {
         transaction: &Transaction,
         geth_trace_result: &TraceResult<GethTrace, String>,
         provider: &RootProvider<PubSubFrontend>,
+        receipts: &HashMap<B256, TransactionReceipt>,
+        codes: &HashMap<Address, Bytes>,
     ) -> Result<()> {
-        let block_number = block.header.number.expect("Block should have a number");
+        let block_number = block.header.number.ok_or(ScanError::MissingBlockNumber)?;
         let block_miner = block.header.miner;
         let block_base_fee_per_gas = U256::from(block.header.base_fee_per_gas.unwrap_or(0));
 
@@ -156,17 +336,26 @@ impl Blockchain {
 
         match geth_trace_result {
             TraceResult::Success { result, tx_hash } => {
-                let tx_hash = tx_hash.expect("Cannot get the transaction hash from geth trace!");
-                let call_trace = result.clone().try_into_call_frame()?;
+                let tx_hash =
+                    tx_hash.ok_or_else(|| ScanError::trace_corrupt(block_number, None))?;
+                let call_trace = result
+                    .clone()
+                    .try_into_call_frame()
+                    .map_err(|_| ScanError::trace_corrupt(block_number, Some(tx_hash)))?;
                 let tx_gas_used = call_trace.gas_used;
                 let transaction_fee_from_sender = tx_gas_used * gas_price;
                 let transaction_fee_to_miner = tx_gas_used * max_priority_fee_per_gas;
+                // The receipt is needed both for the blob fee and for the
+                // `Transfer` logs that drive token taint propagation; it was
+                // prefetched for the whole block in `record_block`.
+                let transaction_receipt =
+                    receipts
+                        .get(&tx_hash)
+                        .ok_or_else(|| ScanError::ReceiptUnavailable {
+                            tx: tx_hash.to_string(),
+                        })?;
                 let mut blob_fee = U256::ZERO;
                 if has_blobs {
-                    let transaction_receipt = provider
-                        .get_transaction_receipt(tx_hash)
-                        .await?
-                        .expect("Cannot get transaction receipt!");
                     let blob_gas_used = transaction_receipt.blob_gas_used.unwrap_or(0_u128);
                     let blob_gas_price = transaction_receipt.blob_gas_price.unwrap_or(0_u128);
                     blob_fee = U256::from(blob_gas_used * blob_gas_price);
@@ -193,17 +382,26 @@ impl Blockchain {
                     .await?;
 
                 // Deal with ETH transfer in Geth Debug trace results using Depth First Traversal
-                self.depth_first_traversal(txn, cache, &call_trace, provider, block_number)
+                self.depth_first_traversal(txn, cache, &call_trace, provider, block_number, codes)
+                    .await?;
+
+                // Deal with ERC-20/ERC-721 transfers recorded in the receipt logs.
+                self.record_token_transfers(txn, cache, transaction_receipt, provider, block_number)
                     .await?;
             }
-            TraceResult::Error { .. } => {
-                eprintln!("Trace Failed")
+            TraceResult::Error { tx_hash, .. } => {
+                // Surface the block/tx context through the error channel so the
+                // caller can decide whether to log, skip, or retry this trace.
+                return Err(ScanError::trace_corrupt(block_number, *tx_hash).into());
             }
         }
 
-        // The address in this transaction is self-destructed, so we set the score to zero.
+        // The address in this transaction is self-destructed, so we set the
+        // score to zero. The zeroed score is flushed with a `BlockSnapshotTable`
+        // row like any other change, so a reorg that orphans this block reverts
+        // it through `undo_block` without needing separate provenance.
         for address in cache.drain_self_destruct() {
-            cache.insert_data(address, Score::new(U256::ZERO, U256::ZERO));
+            cache.insert_data(address, Score::new(U256::ZERO, U256::ZERO)?);
         }
 
         Ok(())
@@ -217,6 +415,7 @@ impl Blockchain {
         root: &CallFrame,
         provider: &RootProvider<PubSubFrontend>,
         block_number: u64,
+        codes: &HashMap<Address, Bytes>,
     ) -> Result<()> {
         // Define a stack to help with Depth First Traversal
         let mut stack: Vec<std::slice::Iter<CallFrame>> = Vec::new();
@@ -225,7 +424,7 @@ impl Blockchain {
         // itself and all its child traces will not be executed.
         if root.error.is_none() {
             stack.push(root.calls.iter());
-            self.process_frame(txn, cache, root, provider, block_number)
+            self.process_frame(txn, cache, root, provider, block_number, codes)
                 .await?;
         }
 
@@ -235,7 +434,7 @@ impl Blockchain {
                 // itself and all its child traces will not be executed.
                 if next_frame.error.is_none() {
                     stack.push(next_frame.calls.iter());
-                    self.process_frame(txn, cache, next_frame, provider, block_number)
+                    self.process_frame(txn, cache, next_frame, provider, block_number, codes)
                         .await?;
                 }
             } else {
@@ -246,6 +445,21 @@ impl Blockchain {
         Ok(())
     }
 
+    /// Collect the addresses that SELFDESTRUCT within a call tree so their code
+    /// can be fetched in the batched pre-pass. Mirrors the DFS pruning rules:
+    /// failed frames (and their children) are skipped.
+    fn collect_selfdestruct_addresses(frame: &CallFrame, addresses: &mut BTreeSet<Address>) {
+        if frame.error.is_some() {
+            return;
+        }
+        if frame.typ == "SELFDESTRUCT" {
+            addresses.insert(frame.from);
+        }
+        for child in frame.calls.iter() {
+            Self::collect_selfdestruct_addresses(child, addresses);
+        }
+    }
+
     /// Process ETH transfer in each Geth Debug trace frame
     async fn process_frame<'a>(
         &self,
@@ -254,6 +468,7 @@ impl Blockchain {
         frame: &CallFrame,
         provider: &RootProvider<PubSubFrontend>,
         block_number: u64,
+        codes: &HashMap<Address, Bytes>,
     ) -> Result<()> {
         let call_type = &frame.typ;
         // The `DELEGATECALL` (`CALLCODE`) and `STATICCALL` call types cannot transfer ETH
@@ -264,11 +479,9 @@ impl Blockchain {
                 // After the self-destruct operation, the address is still a contract address
                 // probably due to the error in self-destruct operation (but not explicitly shown in the trace)
                 // 0x6550f9A4bd878A384625F62Ad5AAb1fE7C3412dE in block 19481732
-                let code = provider
-                    .get_code_at(frame.from)
-                    .block_id(block_number.into())
-                    .await?;
-                if code.len() == 0 {
+                // The code was prefetched for every SELFDESTRUCT frame in `record_block`.
+                let code_len = codes.get(&frame.from).map(|code| code.len()).unwrap_or(0);
+                if code_len == 0 {
                     cache.insert_self_destruct(AddressKey::new(frame.from));
                 }
             }
@@ -280,7 +493,7 @@ impl Blockchain {
                 let address_key_from = AddressKey::new(from_address);
                 let to_address = frame
                     .to
-                    .expect("Cannot get the to address in the trace frame");
+                    .ok_or(ScanError::MissingRecipient { block: block_number })?;
                 let address_key_to = AddressKey::new(to_address);
 
                 self.score_db
@@ -300,6 +513,61 @@ impl Blockchain {
         Ok(())
     }
 
+    /// Decode the ERC-20/ERC-721 `Transfer` logs in a receipt and propagate
+    /// token taint through the per-`(token, address)` balances.
+    async fn record_token_transfers<'a>(
+        &self,
+        txn: &mut MdbxWriteTransaction<'a>,
+        cache: &Cache,
+        receipt: &TransactionReceipt,
+        provider: &RootProvider<PubSubFrontend>,
+        block_number: u64,
+    ) -> Result<()> {
+        for log in receipt.inner.logs() {
+            let topics = log.topics();
+            if topics.is_empty() || topics[0] != TRANSFER_TOPIC {
+                continue;
+            }
+            // ERC-20 `Transfer` indexes `from`/`to` and carries the amount in
+            // data; ERC-721 additionally indexes the token id and moves exactly
+            // one token per event. The token id is not part of the
+            // `(account, token)` key, so NFTs of a collection share a balance.
+            let (from, to, value) = match topics.len() {
+                3 => {
+                    let data = log.data().data.as_ref();
+                    if data.len() < 32 {
+                        continue;
+                    }
+                    (
+                        Address::from_word(topics[1]),
+                        Address::from_word(topics[2]),
+                        U256::from_be_slice(&data[..32]),
+                    )
+                }
+                4 => (
+                    Address::from_word(topics[1]),
+                    Address::from_word(topics[2]),
+                    U256::from(1),
+                ),
+                _ => continue,
+            };
+
+            self.score_db
+                .record_token_transfer(
+                    txn,
+                    cache,
+                    provider,
+                    block_number,
+                    log.address(),
+                    from,
+                    to,
+                    value,
+                )
+                .await?;
+        }
+        Ok(())
+    }
+
     /// Record block/uncle rewards in PoW and beacon withdrawals in PoS.
     async fn record_reward<'a>(
         &self,
@@ -307,14 +575,15 @@ impl Blockchain {
         cache: &Cache,
         block: Block,
         provider: &RootProvider<PubSubFrontend>,
+        uncles: Vec<Option<Block>>,
     ) -> Result<()> {
-        let block_number = block.header.number.expect("Block should have a number");
+        let block_number = block.header.number.ok_or(ScanError::MissingBlockNumber)?;
         let block_miner = block.header.miner;
 
         // Deal with block reward and uncle reward in PoW, and beacon withdrawal in PoS
-        if block_number < POS_BLOCK_NUMBER {
+        if block_number < self.spec.pos_block_number {
             // Calculate static block reward and uncle inclusion reward when PoW
-            let static_block_reward: U256 = parse_units("2", "ether")?.into();
+            let static_block_reward: U256 = self.spec.static_block_reward;
             let uncle_count = block.uncles.len() as u64;
             let uncle_inclusion_reward =
                 static_block_reward / U256::from(32) * U256::from(uncle_count);
@@ -335,12 +604,13 @@ impl Blockchain {
                 )
                 .await?;
 
-            // Update the state of all uncle miners
-            for idx in 0..uncle_count {
-                let uncle_block = provider
-                    .get_uncle(block_number.into(), idx)
-                    .await?
-                    .expect("Cannot get the uncle block!");
+            // Update the state of all uncle miners, using the uncles prefetched
+            // concurrently in `record_block`.
+            for (idx, uncle_block) in uncles.into_iter().enumerate() {
+                let uncle_block = uncle_block.ok_or(ScanError::UncleUnavailable {
+                    block: block_number,
+                    index: idx as u64,
+                })?;
                 let uncle_miner = uncle_block.header.miner;
                 let uncle_number = uncle_block.header.number;
                 if let Some(uncle_number) = uncle_number {
@@ -434,7 +704,8 @@ impl Blockchain {
     pub fn export_historical_amount_of_tainted_addresses(&self) -> Result<()> {
         let txn = self.db.read_transaction();
         self.score_db
-            .export_historical_amount_of_tainted_addresses(&txn)
+            .export_historical_amount_of_tainted_addresses(&txn)?;
+        Ok(())
     }
 
     /// Get tainted addresses until the given block
@@ -443,8 +714,9 @@ impl Blockchain {
         block_number: u64,
     ) -> Result<BTreeMap<Address, Score>> {
         let txn = self.db.read_transaction();
-        self.score_db
-            .export_tainted_addresses_until_block_number(&txn, block_number)
+        Ok(self
+            .score_db
+            .export_tainted_addresses_until_block_number(&txn, block_number)?)
     }
 
     pub async fn export_address_score_between_block_range<'a>(
@@ -456,7 +728,8 @@ impl Blockchain {
         let txn = self.db.read_transaction();
         self.score_db
             .export_address_score_between_block_range(&txn, conn, from_block, to_block)
-            .await
+            .await?;
+        Ok(())
     }
 
     pub async fn get_address_latest_score(
@@ -466,9 +739,10 @@ impl Blockchain {
     ) -> Result<Score> {
         let address = AddressKey::new(address);
         let txn = self.db.read_transaction();
-        self.score_db
+        Ok(self
+            .score_db
             .get_address_latest_score(&txn, provider, &address)
-            .await
+            .await?)
     }
 
     /// Get the score of the given address in the given block
@@ -480,15 +754,45 @@ impl Blockchain {
     ) -> Result<Score> {
         let address = AddressKey::new(address);
         let txn = self.db.read_transaction();
-        self.score_db
+        Ok(self
+            .score_db
             .get_address_score_by_block_number(&txn, provider, &address, block_number)
-            .await
+            .await?)
     }
 
     /// Get the maximum dirty amount of the given address
     pub fn get_address_max_dirty_amount(&self, address: Address) -> Result<Score> {
         let address = AddressKey::new(address);
         let txn = self.db.read_transaction();
-        self.score_db.get_address_max_dirty_amount(&txn, &address)
+        Ok(self.score_db.get_address_max_dirty_amount(&txn, &address)?)
+    }
+
+    /// Populate the per-block USD/ETH price table for a block range from an
+    /// injected price source.
+    pub fn populate_block_prices(
+        &self,
+        source: &impl PriceSource,
+        from_block: u64,
+        to_block: u64,
+    ) {
+        let mut txn = self.db.write_transaction();
+        self.score_db
+            .populate_block_prices(&mut txn, source, from_block, to_block);
+        txn.commit();
+    }
+
+    /// Realized fiat inflow/outflow of tainted value for the given address over
+    /// a block range, using the recorded per-block prices.
+    pub fn get_address_pnl(
+        &self,
+        address: Address,
+        from_block: u64,
+        to_block: u64,
+    ) -> Result<Decimal> {
+        let address = AddressKey::new(address);
+        let txn = self.db.read_transaction();
+        Ok(self
+            .score_db
+            .get_address_pnl(&txn, &address, from_block, to_block)?)
     }
 }
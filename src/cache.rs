@@ -1,62 +1,215 @@
-use crate::primitives::{AddressKey, Score};
+use crate::primitives::{AddressKey, Score, TokenKey};
 use parking_lot::Mutex;
-use std::{collections::BTreeMap, mem, ops::DerefMut};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    mem,
+    ops::DerefMut,
+};
+
+/// Default number of resident address scores kept in memory when no capacity
+/// is configured on the CLI.
+pub const DEFAULT_SCORE_CACHE_CAPACITY: usize = 1 << 20;
+
+/// Exact set of self-destructed addresses. Insertion is `O(1)` on the backing
+/// [`HashSet`] and draining is linear in the entries held, which is all the
+/// hot path — a per-transaction drain-and-zero — needs.
+#[derive(Debug, Default)]
+struct SelfDestructSet {
+    exact: HashSet<AddressKey>,
+}
+
+impl SelfDestructSet {
+    fn new() -> Self {
+        Self::default()
+    }
+
+    fn insert(&mut self, address: AddressKey) {
+        self.exact.insert(address);
+    }
+
+    /// Drain the set.
+    fn drain(&mut self) -> Vec<AddressKey> {
+        self.exact.drain().collect()
+    }
+}
+
+/// Bounded, LRU-ordered set of resident address scores. Recency is tracked
+/// with a monotonic sequence number and a `seq -> address` index so the
+/// least-recently-used entry can be found in `O(log n)`. When an insert would
+/// exceed `capacity` the LRU entry is moved into `evicted`, a buffer of scores
+/// awaiting their next
+/// [`ScoreDb::flush_cache`](crate::score_db::ScoreDb::flush_cache). An evicted
+/// entry is still the authoritative in-flight value for the block, so a read
+/// promotes it back into the resident set rather than falling through to the
+/// stale pre-block score on disk; only entries never touched again are dropped
+/// at flush. This keeps the resident set bounded over long runs without
+/// corrupting a block whose working set exceeds `capacity`.
+#[derive(Debug)]
+struct ScoreCache {
+    capacity: usize,
+    seq: u64,
+    entries: HashMap<AddressKey, (Score, u64)>,
+    recency: BTreeMap<u64, AddressKey>,
+    evicted: HashMap<AddressKey, Score>,
+}
+
+impl ScoreCache {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            seq: 0,
+            entries: HashMap::new(),
+            recency: BTreeMap::new(),
+            evicted: HashMap::new(),
+        }
+    }
+
+    /// Records `address` as the most recently used entry, returning the next
+    /// sequence number to stamp on it.
+    fn touch(&mut self, address: &AddressKey, old_seq: Option<u64>) -> u64 {
+        if let Some(old) = old_seq {
+            self.recency.remove(&old);
+        }
+        self.seq += 1;
+        self.recency.insert(self.seq, address.clone());
+        self.seq
+    }
+
+    fn get(&mut self, address: &AddressKey) -> Option<Score> {
+        if let Some(old_seq) = self.entries.get(address).map(|(_, seq)| *seq) {
+            let seq = self.touch(address, Some(old_seq));
+            let (score, slot) = self.entries.get_mut(address).unwrap();
+            *slot = seq;
+            return Some(*score);
+        }
+        // An address evicted earlier this block but not yet flushed still holds
+        // the latest in-flight score; promote it back into the resident set so
+        // this read and the end-of-block flush see it instead of the stale
+        // pre-block score on disk.
+        if let Some(score) = self.evicted.remove(address) {
+            let seq = self.touch(address, None);
+            self.entries.insert(address.clone(), (score, seq));
+            self.evict_to_capacity();
+            return Some(score);
+        }
+        None
+    }
+
+    fn insert(&mut self, address: AddressKey, score: Score) {
+        // A re-inserted address is resident again, so it no longer awaits a
+        // flush from the eviction buffer.
+        self.evicted.remove(&address);
+        let old_seq = self.entries.get(&address).map(|(_, seq)| *seq);
+        let seq = self.touch(&address, old_seq);
+        self.entries.insert(address, (score, seq));
+        self.evict_to_capacity();
+    }
+
+    /// Moves least-recently-used entries into the flush buffer until the
+    /// resident set is back within `capacity`.
+    fn evict_to_capacity(&mut self) {
+        while self.entries.len() > self.capacity {
+            let Some((&lru_seq, _)) = self.recency.iter().next() else {
+                break;
+            };
+            let victim = self.recency.remove(&lru_seq).unwrap();
+            if let Some((victim_score, _)) = self.entries.remove(&victim) {
+                self.evicted.insert(victim, victim_score);
+            }
+        }
+    }
+
+    fn drain_resident(&mut self) -> BTreeMap<AddressKey, Score> {
+        self.recency.clear();
+        self.seq = 0;
+        self.entries
+            .drain()
+            .map(|(address, (score, _))| (address, score))
+            .collect()
+    }
+
+    fn drain_evicted(&mut self) -> Vec<(AddressKey, Score)> {
+        mem::take(&mut self.evicted).into_iter().collect()
+    }
+}
 
 #[derive(Debug)]
 pub struct Cache {
-    pub data: Mutex<BTreeMap<AddressKey, Score>>,
-    pub self_destruct: Mutex<Vec<AddressKey>>,
+    data: Mutex<ScoreCache>,
+    pub token_data: Mutex<BTreeMap<TokenKey, Score>>,
+    self_destruct: Mutex<SelfDestructSet>,
 }
 
 impl Default for Cache {
-    /// Create a new cache.
+    /// Create a new cache with the default resident capacity.
     fn default() -> Self {
-        Self {
-            data: Mutex::new(BTreeMap::new()),
-            self_destruct: Mutex::new(Vec::new()),
-        }
+        Self::new(DEFAULT_SCORE_CACHE_CAPACITY)
     }
 }
 
 impl Cache {
-    /// Create a new cache.
-    pub fn new() -> Self {
-        Self::default()
+    /// Create a new cache bounding the resident score set to `capacity` entries.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            data: Mutex::new(ScoreCache::new(capacity)),
+            token_data: Mutex::new(BTreeMap::new()),
+            self_destruct: Mutex::new(SelfDestructSet::new()),
+        }
     }
 
-    /// Get the score of the given address.
+    /// Get the score of the given address, bumping its recency.
     pub fn get_data(&self, address: &AddressKey) -> Option<Score> {
-        let cache_lock = self.data.lock();
-        cache_lock.get(address).copied()
+        let mut cache_lock = self.data.lock();
+        cache_lock.get(address)
     }
 
-    /// Insert a new address and its score.
+    /// Insert a new address and its score, evicting the least-recently-used
+    /// entry to the flush queue if the resident set is at capacity.
     pub fn insert_data(&self, address: AddressKey, score: Score) {
         let mut cache_lock = self.data.lock();
         cache_lock.insert(address, score);
     }
 
-    /// Drain the address and score from the cache and turn into a new collection.
+    /// Drain the still-resident addresses and scores into a new collection.
     pub fn drain_data(&self) -> BTreeMap<AddressKey, Score> {
         let mut cache_lock = self.data.lock();
-        mem::take(cache_lock.deref_mut())
+        cache_lock.drain_resident()
+    }
+
+    /// Drain the addresses evicted since the last flush so they can be
+    /// persisted alongside the resident hot set.
+    pub fn drain_evicted_data(&self) -> Vec<(AddressKey, Score)> {
+        let mut cache_lock = self.data.lock();
+        cache_lock.drain_evicted()
+    }
+
+    /// Get the score of the given token balance.
+    pub fn get_token_data(&self, key: &TokenKey) -> Option<Score> {
+        let cache_lock = self.token_data.lock();
+        cache_lock.get(key).copied()
     }
 
-    /// Check if the address is self-destructed.
-    pub fn check_self_destructed(&self, address: &AddressKey) -> bool {
-        let cache_lock = self.self_destruct.lock();
-        cache_lock.contains(address)
+    /// Insert a new token balance and its score.
+    pub fn insert_token_data(&self, key: TokenKey, score: Score) {
+        let mut cache_lock = self.token_data.lock();
+        cache_lock.insert(key, score);
+    }
+
+    /// Drain the token balances and scores from the cache and turn into a new collection.
+    pub fn drain_token_data(&self) -> BTreeMap<TokenKey, Score> {
+        let mut cache_lock = self.token_data.lock();
+        mem::take(cache_lock.deref_mut())
     }
 
     /// Insert a self-destruct address.
     pub fn insert_self_destruct(&self, address: AddressKey) {
         let mut cache_lock = self.self_destruct.lock();
-        cache_lock.push(address);
+        cache_lock.insert(address);
     }
 
     /// Drain the self-destructed addresses from the cache and turn into a new collection.
     pub fn drain_self_destruct(&self) -> Vec<AddressKey> {
         let mut cache_lock = self.self_destruct.lock();
-        cache_lock.drain(..).collect()
+        cache_lock.drain()
     }
 }
@@ -1,11 +1,65 @@
 use clap::Parser;
 
+use crate::cache::DEFAULT_SCORE_CACHE_CAPACITY;
+use crate::policy::PolicyKind;
+
+/// Default number of committed blocks between recovery checkpoints.
+pub const DEFAULT_CHECKPOINT_INTERVAL: u64 = 1000;
+
+/// Default number of upcoming blocks prefetched concurrently.
+pub const DEFAULT_PREFETCH_DEPTH: usize = 8;
+
 #[derive(Parser)]
 #[command(version, name = "uncleanliness")]
 pub struct Cli {
     /// Reset the database.
     #[arg(short = 'r', long)]
     reset: bool,
+
+    /// Path to a chain-spec JSON file defining the taint sources and network
+    /// parameters. Defaults to the built-in Ethereum-mainnet TC spec.
+    #[arg(short = 's', long)]
+    spec: Option<String>,
+
+    /// Taint-propagation policy used to split dirty funds on a transfer.
+    #[arg(short = 'p', long, value_enum, default_value_t = PolicyKind::default())]
+    policy: PolicyKind,
+
+    /// Opt in to the non-default, experimental taint policies (poison, FIFO,
+    /// LIFO). Without this flag only the proportional haircut is allowed.
+    #[arg(long)]
+    experimental_policies: bool,
+
+    /// Maximum number of resident address scores kept in memory. When the
+    /// cache exceeds this bound the least-recently-used entries are moved out
+    /// of the resident set and written to score_db at the end of the block.
+    #[arg(short = 'c', long, default_value_t = DEFAULT_SCORE_CACHE_CAPACITY)]
+    cache_capacity: usize,
+
+    /// Keep following the chain head as new blocks arrive instead of stopping
+    /// at the spec's `end_block_number`, rolling back and re-applying blocks on
+    /// short reorgs so the scores track the canonical chain.
+    #[arg(short = 'f', long)]
+    follow: bool,
+
+    /// Write a consistent recovery checkpoint every this many committed blocks.
+    /// On restart the scan rewinds to the most recent checkpoint, giving
+    /// exactly-once-style recovery instead of best-effort resume.
+    #[arg(short = 'k', long, default_value_t = DEFAULT_CHECKPOINT_INTERVAL)]
+    checkpoint_interval: u64,
+
+    /// Number of upcoming blocks to prefetch concurrently. A depth above 1
+    /// overlaps the slow RPC round-trips with local scoring; blocks are still
+    /// committed in strict block-number order so results are unchanged.
+    #[arg(short = 'd', long, default_value_t = DEFAULT_PREFETCH_DEPTH)]
+    prefetch_depth: usize,
+
+    /// Extend the scored history backward down to this block instead of running
+    /// the forward scan, filling in blocks older than the earliest one already
+    /// covered. The backfill commits one block at a time and resumes where a
+    /// previous interrupted backfill left off.
+    #[arg(short = 'b', long)]
+    backfill_to: Option<u64>,
 }
 
 impl Cli {
@@ -13,4 +67,44 @@ impl Cli {
     pub fn is_reset(&self) -> bool {
         self.reset
     }
+
+    /// Path to the chain-spec JSON file, if one was supplied.
+    pub fn spec_path(&self) -> Option<&str> {
+        self.spec.as_deref()
+    }
+
+    /// The selected taint-propagation policy.
+    pub fn policy(&self) -> PolicyKind {
+        self.policy
+    }
+
+    /// Whether the experimental taint policies have been opted into.
+    pub fn experimental_policies(&self) -> bool {
+        self.experimental_policies
+    }
+
+    /// Maximum number of resident address scores kept in memory.
+    pub fn cache_capacity(&self) -> usize {
+        self.cache_capacity
+    }
+
+    /// Whether to keep following the chain head after the initial range.
+    pub fn is_follow(&self) -> bool {
+        self.follow
+    }
+
+    /// Number of committed blocks between recovery checkpoints.
+    pub fn checkpoint_interval(&self) -> u64 {
+        self.checkpoint_interval
+    }
+
+    /// Number of upcoming blocks to prefetch concurrently.
+    pub fn prefetch_depth(&self) -> usize {
+        self.prefetch_depth
+    }
+
+    /// Lowest block to backfill history down to, if backfill mode was selected.
+    pub fn backfill_to(&self) -> Option<u64> {
+        self.backfill_to
+    }
 }
@@ -0,0 +1,102 @@
+use alloy::primitives::{FixedBytes, U256};
+use thiserror::Error;
+
+/// Errors raised while scanning and scoring blocks.
+///
+/// These replace the previous `expect`/`assert!`-on-corruption behaviour so
+/// that a single malformed trace, missing receipt, or violated accounting
+/// invariant can be logged, skipped, or retried by the caller instead of
+/// aborting a multi-million-block run.
+#[derive(Debug, Error)]
+pub enum ScanError {
+    /// A block returned by the node did not carry a block number.
+    #[error("block is missing its number")]
+    MissingBlockNumber,
+
+    /// The block's transactions could not be read as full transactions.
+    #[error("block {block} does not expose its transactions")]
+    MissingBlockTransactions { block: u64 },
+
+    /// A geth trace was unavailable or could not be decoded for a transaction.
+    #[error("trace corrupt in block {block} for tx {tx}")]
+    TraceCorrupt { block: u64, tx: String },
+
+    /// A call frame transferred value but carried no recipient.
+    #[error("trace frame in block {block} has no recipient")]
+    MissingRecipient { block: u64 },
+
+    /// A transaction receipt that should exist could not be fetched.
+    #[error("receipt unavailable for tx {tx}")]
+    ReceiptUnavailable { tx: String },
+
+    /// An uncle block referenced by the header could not be fetched.
+    #[error("uncle {index} unavailable for block {block}")]
+    UncleUnavailable { block: u64, index: u64 },
+
+    /// The `dirty_amount <= balance` accounting invariant was violated.
+    #[error("score invariant violated (dirty {dirty} > balance {balance})")]
+    ScoreInvariant { balance: U256, dirty: U256 },
+
+    /// A block fetch kept failing after the retry budget was exhausted.
+    #[error("block {block} unavailable after {attempts} attempts: {cause}")]
+    BlockUnavailable {
+        block: u64,
+        attempts: u32,
+        cause: eyre::Report,
+    },
+
+    /// The WebSocket connection could not be re-established.
+    #[error("reconnecting to the node failed: {cause}")]
+    ReconnectFailed { cause: eyre::Report },
+}
+
+/// Errors raised by [`ScoreDb`](crate::score_db::ScoreDb) while reading,
+/// mutating, or exporting the on-disk score state.
+///
+/// These replace the former `assert!`/`.unwrap()`/`.expect()` failure points so
+/// that database corruption (a snapshot the provenance tables say must exist
+/// but does not) is reported distinctly from a genuine accounting-invariant
+/// violation, and a single malformed value during a multi-hour export no longer
+/// unwinds the whole run.
+#[derive(Debug, Error)]
+pub enum ScoreDbError {
+    /// A snapshot row referenced by the provenance tables was not found, i.e.
+    /// the on-disk state is internally inconsistent.
+    #[error("snapshot missing for {address} at block {block}")]
+    SnapshotMissing { block: u64, address: String },
+
+    /// The score database is otherwise internally inconsistent.
+    #[error("score database state is corrupt: {0}")]
+    StateCorrupt(String),
+
+    /// A transfer tried to move more value than the sender's balance.
+    #[error("balance underflow: cannot transfer {transfer} from balance {balance}")]
+    BalanceUnderflow { balance: U256, transfer: U256 },
+
+    /// A scoring invariant bubbled up from the score primitives or a policy.
+    #[error(transparent)]
+    Scan(#[from] ScanError),
+
+    /// An RPC query issued while scoring failed.
+    #[error("provider query failed: {0}")]
+    Provider(eyre::Report),
+
+    /// A PostgreSQL export query failed.
+    #[error("postgres export failed: {0}")]
+    Postgres(#[from] tokio_postgres::Error),
+
+    /// A value could not be serialized for export (malformed decimal, CSV or
+    /// I/O error).
+    #[error("export serialization failed: {0}")]
+    Serialize(String),
+}
+
+impl ScanError {
+    /// Builds a [`ScanError::TraceCorrupt`] for a transaction hash.
+    pub fn trace_corrupt(block: u64, tx: Option<FixedBytes<32>>) -> Self {
+        ScanError::TraceCorrupt {
+            block,
+            tx: tx.map(|h| h.to_string()).unwrap_or_else(|| "<unknown>".to_string()),
+        }
+    }
+}
@@ -2,19 +2,25 @@ pub mod blockchain;
 pub mod cache;
 pub mod cli;
 pub mod constant;
+pub mod error;
+pub mod policy;
 pub mod primitives;
+pub mod rpc;
 pub mod score_db;
+pub mod spec;
 
-use alloy::providers::{Provider, ProviderBuilder, WsConnect};
+use alloy::{primitives::B256, providers::Provider};
 use blockchain::Blockchain;
 use clap::Parser;
 use cli::Cli;
-use constant::{INI_BLOCK_NUMBER_TC, INI_BLOCK_NUMBER_BYBIT, POS_BLOCK_NUMBER, END_BLOCK_NUMBER};
 use dotenvy::dotenv;
+use error::ScanError;
 use eyre::Result;
+use futures::stream::{FuturesOrdered, StreamExt};
 use nimiq_database::mdbx::MdbxDatabase;
-use std::{env, sync::Arc};
-use tokio::sync::{Mutex, Notify};
+use rpc::{fetch_block, ResilientProvider};
+use spec::ChainSpec;
+use std::{collections::BTreeMap, env};
 
 #[tokio::main]
 async fn main() -> Result<()> {
@@ -22,85 +28,222 @@ async fn main() -> Result<()> {
     dotenv().expect(".env file not found");
     // Get the RPC URL from the environment variable.
     let rpc_url: &str = &env::var("LOCAL_WS_URL").expect("URL must be set");
-    // Create a provider.
-    let provider = ProviderBuilder::new()
-        .on_ws(WsConnect::new(rpc_url))
-        .await?;
+    // Create a crash-resilient provider that retries and reconnects so a long
+    // unattended run can survive transient WebSocket drops.
+    let mut provider = ResilientProvider::connect(rpc_url).await?;
 
     // Create a database under the directory "./database".
     let db = MdbxDatabase::new("./database/database_final", Default::default())?;
 
-    // Get the current blockchain state
-    let blockchain = Blockchain::load(db.clone());
-    let init_block_number = blockchain.get_last_block_number();
     // Get the command from CLI
     let cli = Cli::parse();
+    // Load the chain spec (taint sources, network params, scan range).
+    let spec = match cli.spec_path() {
+        Some(path) => ChainSpec::from_file(path)?,
+        None => ChainSpec::mainnet(),
+    };
+
+    let policy = cli.policy();
+    // The non-default policies change the scored output materially, so they
+    // must be opted into explicitly rather than reached by a stray `-p`.
+    if policy.is_experimental() && !cli.experimental_policies() {
+        eyre::bail!(
+            "the {:?} policy is experimental; pass --experimental-policies to use it",
+            policy
+        );
+    }
+    let cache_capacity = cli.cache_capacity();
+    let checkpoint_interval = cli.checkpoint_interval();
+
+    // Get the current blockchain state
+    let blockchain = Blockchain::load(db.clone(), spec.clone(), policy, cache_capacity);
+    let init_block_number = blockchain.get_last_block_number();
     let (blockchain, block_number) = if cli.is_reset() || init_block_number.is_none() {
         (
-            Blockchain::init_new(db, &provider, INI_BLOCK_NUMBER_TC - 1).await?,
-            INI_BLOCK_NUMBER_TC,
+            Blockchain::init_new(
+                db,
+                provider.provider(),
+                spec.clone(),
+                policy,
+                cache_capacity,
+                spec.ini_block_number - 1,
+            )
+            .await?,
+            spec.ini_block_number,
         )
     } else {
-        // The default mode is resuming from the current state.
-        (Blockchain::load(db), init_block_number.unwrap() + 1)
+        // The default mode is resuming from the most recent complete
+        // checkpoint, discarding any committed blocks past it.
+        let blockchain = Blockchain::load(db, spec.clone(), policy, cache_capacity);
+        // Refuse to resume into a database built under a different policy or an
+        // incompatible on-disk format.
+        blockchain.verify_meta()?;
+        let resume_block_number = blockchain.resume_from_checkpoint()?;
+        (blockchain, resume_block_number)
     };
 
-    // let latest_block_number = Arc::new(Mutex::new(POS_BLOCK_NUMBER));
-    // let notify = Arc::new(Notify::new());
-
-    // let provider_clone = provider.clone();
-    // let latest_block_number_clone = Arc::clone(&latest_block_number);
-    // let notify_clone = Arc::clone(&notify);
-
-    // tokio::spawn(async move {
-    //     // Subscribe to new blocks.
-    //     let mut block_subscription = provider_clone
-    //         .subscribe_blocks()
-    //         .await
-    //         .expect("Cannot subscribe the latest block!");
-    //     // Set the block number upon receiving a new block.
-    //     while let Ok(block) = block_subscription.recv().await {
-    //         let new_block_number = block
-    //             .header
-    //             .number
-    //             .expect("Cannot get the latest block number!");
-    //         let mut latest_block_number = latest_block_number_clone.lock().await;
-    //         *latest_block_number = new_block_number;
-    //         notify_clone.notify_one();
-    //     }
-    // });
+    // Wire Ctrl-C to the scan's abort flag so an unattended run stops after
+    // committing its in-flight block instead of losing it to a hard kill.
+    let abort = blockchain.abort_handle();
+    tokio::spawn(async move {
+        if tokio::signal::ctrl_c().await.is_ok() {
+            abort.store(true, std::sync::atomic::Ordering::Relaxed);
+        }
+    });
+
+    // Backfill mode extends the history downward and exits; it shares the same
+    // abort handling so it can be interrupted and resumed.
+    if let Some(to_block) = cli.backfill_to() {
+        let from_block = block_number.saturating_sub(1);
+        println!("Backfilling {} down to {} ...", from_block, to_block);
+        blockchain
+            .backfill_blocks(provider.provider(), from_block, to_block)
+            .await?;
+        return Ok(());
+    }
 
     let mut current_block_number = block_number;
     println!("Start from   {}   ...", current_block_number);
-    // loop {
-    //     {
-    //         let latest_block_number = latest_block_number.lock().await;
-    //         // Trace each block
-    //         while current_block_number <= *latest_block_number {
-    //             // Get the target block using block number.
-    //             let target_block = provider
-    //                 .get_block_by_number(current_block_number.into(), true)
-    //                 .await?
-    //                 .expect("Cannot get the target block!");
-
-    //             blockchain.record_block(target_block, &provider).await?;
-    //             current_block_number += 1;
-    //         }
-    //     }
-    //     notify.notified().await;
-    // }
-
-    let latest_block_number = END_BLOCK_NUMBER;
-    while current_block_number <= latest_block_number {
-        // Get the target block using block number.
-        let target_block = provider
-            .get_block_by_number(current_block_number.into(), true)
-            .await?
-            .expect("Cannot get the target block!");
-
-        blockchain.record_block(target_block, &provider).await?;
-        current_block_number += 1;
+
+    // Recently recorded block hashes keyed by number, used to detect reorgs by
+    // comparing each new block's `parent_hash` against the stored hash of its
+    // predecessor. Bounded to the last `REORG_RING_DEPTH` blocks.
+    let mut ring: BTreeMap<u64, B256> = BTreeMap::new();
+
+    let prefetch_depth = cli.prefetch_depth().max(1);
+    let backoff = provider.backoff();
+
+    // Catch up to the spec's end block, then — in `--follow` mode — keep going
+    // as the chain head advances instead of stopping there.
+    let mut head_block_number = spec.end_block_number;
+    'pipeline: loop {
+        // Prefetch up to `prefetch_depth` upcoming blocks concurrently, keeping
+        // them in strict block-number order via `FuturesOrdered` so the network
+        // round-trips overlap scoring while commits stay deterministic.
+        let mut in_flight = FuturesOrdered::new();
+        let mut next_to_fetch = current_block_number;
+        while next_to_fetch <= head_block_number && in_flight.len() < prefetch_depth {
+            let handle = provider.inner();
+            let block_number = next_to_fetch;
+            in_flight.push_back(async move { fetch_block(&handle, backoff, block_number).await });
+            next_to_fetch += 1;
+        }
+
+        while let Some(result) = in_flight.next().await {
+            // Results arrive in submission order, so this is the block for
+            // `current_block_number`. A fetch that spent its retry budget on the
+            // shared handle is retried through `get_block`, which reconnects the
+            // socket if it has dropped.
+            let target_block = match result {
+                Ok(block) => block,
+                Err(_) => provider.get_block(current_block_number).await?,
+            };
+
+            // Reorg check: if our recorded predecessor is no longer this block's
+            // parent, rewind to the common ancestor and rebuild the pipeline.
+            if let Some(stored_parent) = ring.get(&(current_block_number - 1)).copied() {
+                if stored_parent != target_block.header.parent_hash {
+                    let ancestor =
+                        find_common_ancestor(&mut provider, &ring, current_block_number - 1).await?;
+                    let orphaned: Vec<u64> =
+                        ring.range((ancestor + 1)..).map(|(n, _)| *n).collect();
+                    println!(
+                        "Reorg at {}: rolling back {} block(s) to {}",
+                        current_block_number,
+                        orphaned.len(),
+                        ancestor
+                    );
+                    // Undo newest-first so each address reverts through its own
+                    // history in order.
+                    for orphan in orphaned.into_iter().rev() {
+                        blockchain.undo_block(orphan)?;
+                        ring.remove(&orphan);
+                    }
+                    current_block_number = ancestor + 1;
+                    // Drop the now-stale prefetch window and re-prime from the
+                    // common ancestor.
+                    continue 'pipeline;
+                }
+            }
+
+            let block_hash = target_block.header.hash;
+            let aborted = blockchain
+                .record_block(target_block, provider.provider())
+                .await?;
+            ring.insert(current_block_number, block_hash);
+            while ring.len() > REORG_RING_DEPTH {
+                let oldest = *ring.keys().next().expect("ring is non-empty");
+                ring.remove(&oldest);
+            }
+
+            // Persist a recovery checkpoint every `checkpoint_interval` blocks.
+            if checkpoint_interval != 0 && current_block_number % checkpoint_interval == 0 {
+                blockchain.checkpoint(current_block_number)?;
+            }
+            current_block_number += 1;
+
+            // A stop was requested: this block is already committed, so end the
+            // run cleanly instead of starting the next one.
+            if aborted {
+                println!("Aborted after {} ...", current_block_number - 1);
+                break 'pipeline;
+            }
+
+            // Top the window back up now that a slot has freed.
+            while next_to_fetch <= head_block_number && in_flight.len() < prefetch_depth {
+                let handle = provider.inner();
+                let block_number = next_to_fetch;
+                in_flight
+                    .push_back(async move { fetch_block(&handle, backoff, block_number).await });
+                next_to_fetch += 1;
+            }
+        }
+
+        if !cli.is_follow() {
+            break 'pipeline;
+        }
+        // Block until the head advances past what we have already processed.
+        head_block_number = wait_for_new_head(&provider, head_block_number).await?;
     }
 
     Ok(())
 }
+
+/// Number of recent block hashes retained for reorg detection. 128 blocks
+/// comfortably covers the short reorgs seen on mainnet.
+const REORG_RING_DEPTH: usize = 128;
+
+/// Walks back from `from` comparing each recorded hash against the canonical
+/// hash reported by the node, returning the highest block number on which the
+/// two agree — the point from which orphaned blocks must be replayed.
+async fn find_common_ancestor(
+    provider: &mut ResilientProvider,
+    ring: &BTreeMap<u64, B256>,
+    from: u64,
+) -> Result<u64> {
+    let mut number = from;
+    loop {
+        let Some(stored) = ring.get(&number).copied() else {
+            // Nothing recorded this far back; treat it as the ancestor.
+            return Ok(number);
+        };
+        let canonical = provider.get_block(number).await?;
+        if canonical.header.hash == stored || number == 0 {
+            return Ok(number);
+        }
+        number -= 1;
+    }
+}
+
+/// Subscribes to new block headers and returns the first head number strictly
+/// greater than `current`, so the follow loop only wakes for real progress.
+async fn wait_for_new_head(provider: &ResilientProvider, current: u64) -> Result<u64> {
+    let mut subscription = provider.provider().subscribe_blocks().await?;
+    loop {
+        let block = subscription.recv().await?;
+        let number = block.header.number.ok_or(ScanError::MissingBlockNumber)?;
+        if number > current {
+            return Ok(number);
+        }
+    }
+}
@@ -3,7 +3,7 @@ use nimiq_database_value::{AsDatabaseBytes, FromDatabaseBytes};
 use std::{borrow::Cow, ops::Deref};
 
 /// A wrapper around alloy's `Address` so we can implement our own traits.
-#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
 pub struct AddressKey(Address);
 
 impl AddressKey {
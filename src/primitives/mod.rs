@@ -0,0 +1,11 @@
+mod address;
+mod price;
+mod scan_progress;
+mod score;
+mod token;
+
+pub use address::AddressKey;
+pub use price::UsdPrice;
+pub use scan_progress::{ScanProgress, ScanStatus};
+pub use score::Score;
+pub use token::TokenKey;
@@ -0,0 +1,20 @@
+use alloy::primitives::U256;
+use nimiq_database_value_derive::DbSerializable;
+use nimiq_serde::{Deserialize, Serialize};
+
+/// A USD/ETH exchange rate for a block, scaled by `10^18` so it can be stored
+/// as an integer without losing sub-dollar precision.
+///
+/// A fiat amount is recovered from a wei balance with
+/// `balance * usd_per_eth_scaled / 10^36`: one `10^18` divides out the wei
+/// scale and the other the price scale, leaving whole USD.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, DbSerializable)]
+pub struct UsdPrice {
+    pub usd_per_eth_scaled: U256,
+}
+
+impl UsdPrice {
+    pub fn new(usd_per_eth_scaled: U256) -> Self {
+        Self { usd_per_eth_scaled }
+    }
+}
@@ -0,0 +1,49 @@
+use nimiq_database_value_derive::DbSerializable;
+use nimiq_serde::{Deserialize, Serialize};
+
+/// Phase of a resumable scan, persisted alongside the covered block range so an
+/// interrupted run can tell whether it stopped mid-forward-scan, mid-backfill,
+/// or came to a clean stop.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, DbSerializable)]
+pub enum ScanStatus {
+    /// A forward scan is extending the covered range upward.
+    Forward,
+    /// A backfill is extending the covered range downward.
+    Backfill,
+    /// The scan was asked to stop and flushed the in-flight block cleanly.
+    Aborted,
+}
+
+/// The block range a scan has covered so far together with its current phase.
+///
+/// Both forward scanning and backward backfill simply widen `[lowest, highest]`
+/// one block at a time: a backfill continues below `lowest`, `highest` records
+/// the top of the forward range (it is not lowered when a block is undone; the
+/// forward scan resumes from its checkpoint, not from `highest`), and
+/// [`ScanStatus::Aborted`] marks a range that stopped early but whose last block
+/// was committed.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, DbSerializable)]
+pub struct ScanProgress {
+    pub lowest_block: u64,
+    pub highest_block: u64,
+    pub status: ScanStatus,
+}
+
+impl ScanProgress {
+    /// Starts a fresh forward-scan envelope covering a single block.
+    pub fn started(block_number: u64) -> Self {
+        Self {
+            lowest_block: block_number,
+            highest_block: block_number,
+            status: ScanStatus::Forward,
+        }
+    }
+
+    /// Widens the envelope to include `block_number`, recording the phase it was
+    /// reached in.
+    pub fn cover(&mut self, block_number: u64, status: ScanStatus) {
+        self.lowest_block = self.lowest_block.min(block_number);
+        self.highest_block = self.highest_block.max(block_number);
+        self.status = status;
+    }
+}
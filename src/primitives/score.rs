@@ -4,6 +4,8 @@ use alloy::primitives::{U256, U512};
 use nimiq_database_value_derive::DbSerializable;
 use nimiq_serde::{Deserialize, Serialize};
 
+use crate::error::ScanError;
+
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, DbSerializable)]
 pub struct Score {
     pub balance: U256,
@@ -12,28 +14,34 @@ pub struct Score {
 
 impl Score {
     /// Manually set score.
-    /// Panics if `dirty_amount > balance`
-    pub fn new(balance: U256, dirty_amount: U256) -> Self {
-        assert!(
-            dirty_amount <= balance,
-            "Dirty amount must be <= balance ({} < {})",
-            dirty_amount,
-            balance
-        );
-        Self {
+    /// Returns [`ScanError::ScoreInvariant`] if `dirty_amount > balance`.
+    pub fn new(balance: U256, dirty_amount: U256) -> Result<Self, ScanError> {
+        if dirty_amount > balance {
+            return Err(ScanError::ScoreInvariant {
+                balance,
+                dirty: dirty_amount,
+            });
+        }
+        Ok(Self {
             balance,
             dirty_amount,
-        }
+        })
     }
 
     /// Creates a clean score.
     pub fn new_clean(balance: U256) -> Self {
-        Self::new(balance, U256::ZERO)
+        Self {
+            balance,
+            dirty_amount: U256::ZERO,
+        }
     }
 
     /// Creates a fully dirty score.
     pub fn new_dirty(balance: U256) -> Self {
-        Self::new(balance, balance)
+        Self {
+            balance,
+            dirty_amount: balance,
+        }
     }
 
     /// Creates a new score with a given `balance`
@@ -41,7 +49,7 @@ impl Score {
     /// As a design choice, we use a ceiling division here.
     /// This will have the effect that we might overestimate the uncleanliness
     /// slightly (by a fraction of a coin).
-    pub fn with_same_uncleanliness_ceil(balance: U256, proportion: &Self) -> Self {
+    pub fn with_same_uncleanliness_ceil(balance: U256, proportion: &Self) -> Result<Self, ScanError> {
         let dirty_amount = (U512::from(balance) * U512::from(proportion.dirty_amount))
             .div_ceil(U512::from(proportion.balance));
         Self::new(balance, U256::from(dirty_amount))
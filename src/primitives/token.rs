@@ -0,0 +1,52 @@
+use alloy::primitives::Address;
+use nimiq_database_value::{AsDatabaseBytes, FromDatabaseBytes};
+use std::borrow::Cow;
+
+/// Identifies a token balance held by an account: the `(account, token)` pair.
+///
+/// Native ETH balances are keyed by [`AddressKey`](crate::primitives::AddressKey);
+/// ERC-20/ERC-721 balances need the token contract as part of the key so the same
+/// account can carry independent taint per token.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct TokenKey {
+    account: Address,
+    token: Address,
+}
+
+impl TokenKey {
+    pub fn new(account: Address, token: Address) -> Self {
+        Self { account, token }
+    }
+
+    /// The account holding the token balance.
+    pub fn account(&self) -> Address {
+        self.account
+    }
+
+    /// The token contract.
+    pub fn token(&self) -> Address {
+        self.token
+    }
+}
+
+impl AsDatabaseBytes for TokenKey {
+    fn as_key_bytes(&self) -> Cow<[u8]> {
+        let mut bytes = Vec::with_capacity(40);
+        bytes.extend_from_slice(self.account.as_ref());
+        bytes.extend_from_slice(self.token.as_ref());
+        Cow::Owned(bytes)
+    }
+    const FIXED_SIZE: Option<usize> = Some(40);
+}
+
+impl FromDatabaseBytes for TokenKey {
+    fn from_key_bytes(bytes: &[u8]) -> Self
+    where
+        Self: Sized,
+    {
+        Self {
+            account: Address::try_from(&bytes[..20]).expect("Invalid account format"),
+            token: Address::try_from(&bytes[20..40]).expect("Invalid token format"),
+        }
+    }
+}
@@ -0,0 +1,154 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use alloy::{
+    providers::{Provider, ProviderBuilder, RootProvider, WsConnect},
+    pubsub::PubSubFrontend,
+    rpc::types::Block,
+};
+use eyre::Result;
+use tokio::time::sleep;
+
+use crate::error::ScanError;
+
+/// Exponential-backoff schedule used when a block fetch fails or the socket
+/// drops. Delays start at `base`, double on every attempt up to `cap`, and
+/// carry a small random jitter so a reconnecting fleet does not stampede the
+/// node in lock-step.
+#[derive(Debug, Clone, Copy)]
+pub struct Backoff {
+    base: Duration,
+    cap: Duration,
+    max_attempts: u32,
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Self {
+            base: Duration::from_millis(100),
+            cap: Duration::from_secs(30),
+            max_attempts: 8,
+        }
+    }
+}
+
+impl Backoff {
+    /// Delay before the `attempt`-th retry (0-indexed), clamped to `cap` and
+    /// nudged by up to 25% of jitter.
+    pub(crate) fn delay(&self, attempt: u32) -> Duration {
+        let doubled = self.base.saturating_mul(1u32 << attempt.min(20));
+        let capped = doubled.min(self.cap);
+        let jitter = (capped.as_millis() as u64 / 4).max(1);
+        capped + Duration::from_millis(jitter_nanos() % jitter)
+    }
+}
+
+/// Cheap, dependency-free entropy source for backoff jitter.
+fn jitter_nanos() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.subsec_nanos() as u64)
+        .unwrap_or(0)
+}
+
+/// Fetches a single block over `provider`, retrying transient failures with
+/// exponential `backoff` but without reconnecting — suitable for the many
+/// concurrent in-flight futures of the prefetch pipeline, which share a cloned
+/// provider handle. Surfaces [`ScanError::BlockUnavailable`] once the retry
+/// budget is spent.
+pub async fn fetch_block(
+    provider: &RootProvider<PubSubFrontend>,
+    backoff: Backoff,
+    block_number: u64,
+) -> Result<Block, ScanError> {
+    let mut last_err: Option<eyre::Report> = None;
+    for attempt in 0..backoff.max_attempts {
+        if attempt > 0 {
+            sleep(backoff.delay(attempt - 1)).await;
+        }
+        match provider
+            .get_block_by_number(block_number.into(), true)
+            .await
+        {
+            Ok(Some(block)) => return Ok(block),
+            Ok(None) => last_err = Some(eyre::eyre!("node returned no block")),
+            Err(err) => last_err = Some(err.into()),
+        }
+    }
+    Err(ScanError::BlockUnavailable {
+        block: block_number,
+        attempts: backoff.max_attempts,
+        cause: last_err.unwrap_or_else(|| eyre::eyre!("unknown RPC failure")),
+    })
+}
+
+/// A [`RootProvider`] wrapper that survives transient WebSocket drops and RPC
+/// timeouts. Block fetches are retried with exponential backoff, and the
+/// underlying connection is transparently re-established when the socket
+/// closes so a multi-million-block scan can resume at the current block
+/// instead of aborting the whole run.
+pub struct ResilientProvider {
+    ws_url: String,
+    provider: RootProvider<PubSubFrontend>,
+    backoff: Backoff,
+}
+
+impl ResilientProvider {
+    /// Opens an initial connection to `ws_url`.
+    pub async fn connect(ws_url: impl Into<String>) -> Result<Self> {
+        let ws_url = ws_url.into();
+        let provider = Self::build(&ws_url).await?;
+        Ok(Self {
+            ws_url,
+            provider,
+            backoff: Backoff::default(),
+        })
+    }
+
+    async fn build(ws_url: &str) -> Result<RootProvider<PubSubFrontend>> {
+        Ok(ProviderBuilder::new()
+            .on_ws(WsConnect::new(ws_url))
+            .await?)
+    }
+
+    /// The live provider, for callers that issue their own RPC queries.
+    pub fn provider(&self) -> &RootProvider<PubSubFrontend> {
+        &self.provider
+    }
+
+    /// A cheap clone of the live provider handle, for the concurrent fetch
+    /// futures driven by [`fetch_block`].
+    pub fn inner(&self) -> RootProvider<PubSubFrontend> {
+        self.provider.clone()
+    }
+
+    /// The retry schedule this provider applies to block fetches.
+    pub fn backoff(&self) -> Backoff {
+        self.backoff
+    }
+
+    /// Re-establishes the WebSocket connection, swapping in the fresh provider
+    /// on success.
+    async fn reconnect(&mut self) -> Result<(), ScanError> {
+        self.provider = Self::build(&self.ws_url)
+            .await
+            .map_err(|source| ScanError::ReconnectFailed { cause: source })?;
+        Ok(())
+    }
+
+    /// Fetches a full block, retrying transient failures with exponential
+    /// backoff; if the whole retry budget is spent, reconnects once and runs
+    /// the retry budget again. Surfaces
+    /// [`ScanError::BlockUnavailable`] once the retry budget is spent rather
+    /// than panicking.
+    pub async fn get_block(&mut self, block_number: u64) -> Result<Block, ScanError> {
+        // First try against the live socket; if the whole retry budget is spent
+        // the socket is likely dead, so re-establish it and try once more.
+        match fetch_block(&self.provider, self.backoff, block_number).await {
+            Ok(block) => Ok(block),
+            Err(_) => {
+                self.reconnect().await?;
+                fetch_block(&self.provider, self.backoff, block_number).await
+            }
+        }
+    }
+}
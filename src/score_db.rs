@@ -2,6 +2,7 @@ use alloy::{
     primitives::{Address, U256},
     providers::{Provider, RootProvider},
     pubsub::PubSubFrontend,
+    sol,
 };
 use bb8::PooledConnection;
 use bb8_postgres::PostgresConnectionManager;
@@ -17,44 +18,161 @@ use rust_decimal::prelude::*;
 use std::{
     collections::{BTreeMap, BTreeSet},
     fs::File,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
 };
 use tokio_postgres::{Client as PostgresClient, NoTls};
 
 use crate::{
     cache::Cache,
     constant::{TC_ETH_ADDRESS, BYBIT_EXPLOITER_ADDRESS},
-    primitives::{AddressKey, Score},
+    error::ScoreDbError,
+    policy::{PolicyKind, TaintPolicy},
+    primitives::{AddressKey, ScanProgress, ScanStatus, Score, TokenKey, UsdPrice},
 };
 
+/// Source of historical USD/ETH prices used to value scores in fiat. The price
+/// is scaled by `10^18` (see [`UsdPrice`]). Implementors might wrap a price
+/// oracle, a downloaded series, or a provider call.
+pub trait PriceSource {
+    /// The USD/ETH price (scaled by `10^18`) effective at `block_number`, or
+    /// `None` if the source has no data for that block.
+    fn price_at_block(&self, block_number: u64) -> Option<U256>;
+}
+
 // Current score.
 declare_table!(AddressScoreTable, "address_score", AddressKey => Score);
 // Score at given block *after* block transition.
 declare_table!(BlockSnapshotTable, "block_snapshots", u64 => AddressKey => Score);
 // Blocks at which the address has changed.
 declare_table!(AddressHistoryTable, "address_history", AddressKey => dup(u64));
+// Current score of each `(account, token)` ERC-20/ERC-721 balance.
+declare_table!(TokenScoreTable, "token_score", TokenKey => Score);
+// Token score of each `(account, token)` balance *after* block transition.
+declare_table!(BlockTokenSnapshotTable, "block_token_snapshots", u64 => TokenKey => Score);
+// Blocks at which a token balance has changed.
+declare_table!(TokenHistoryTable, "token_history", TokenKey => dup(u64));
+// Height of the most recent complete checkpoint (single-row table).
+declare_table!(CheckpointTable, "checkpoint", u8 => u64);
+// Covered block range and phase of a resumable scan (single-row table).
+declare_table!(ScanProgressTable, "scan_progress", u8 => ScanProgress);
+// On-disk format version and the policy tag a database was built under.
+declare_table!(MetaTable, "meta", u8 => u8);
+// Historical USD/ETH price per block, for fiat valuation of scores.
+declare_table!(BlockPriceTable, "block_price", u64 => UsdPrice);
+
+/// Constant key under which the single-row [`CheckpointTable`] is stored.
+const CHECKPOINT_KEY: u8 = 0;
+
+/// Constant key under which the single-row [`ScanProgressTable`] is stored.
+const SCAN_PROGRESS_KEY: u8 = 0;
+
+/// [`MetaTable`] key holding the on-disk format version.
+const META_FORMAT_KEY: u8 = 0;
+/// [`MetaTable`] key holding the [`PolicyKind::tag`] a database was built with.
+const META_POLICY_KEY: u8 = 1;
+
+/// Current on-disk score format, recorded once per database in [`MetaTable`]
+/// (not per record). Version `0` is the scalar `(balance, dirty_amount)`
+/// layout, which is the only format: every policy, including the dirty-priority
+/// [`Fifo`](crate::policy::Fifo), operates on this scalar score, so no
+/// lot-list layout was introduced. Databases without a recorded version are
+/// assumed to be v0 so they keep loading untouched, while new runs stamp the
+/// version they wrote so an incompatible future layout can be detected.
+const SCORE_FORMAT_VERSION: u8 = 0;
+
+sol! {
+    #[sol(rpc)]
+    contract IERC20 {
+        function balanceOf(address account) external view returns (uint256);
+    }
+}
 
-pub struct ScoreDb;
+pub struct ScoreDb {
+    /// Taint-propagation policy selected for this scan.
+    policy: PolicyKind,
+    /// Set by a signal handler to ask an in-flight scan to stop; checked at the
+    /// start of every [`Self::flush_cache`] so the current block is committed
+    /// before the run returns.
+    abort: Arc<AtomicBool>,
+}
 
 impl ScoreDb {
-    pub fn new(db: MdbxDatabase) -> Self {
+    pub fn new(db: MdbxDatabase, policy: PolicyKind) -> Self {
         db.create_regular_table(&AddressScoreTable);
         db.create_dup_table(&BlockSnapshotTable);
         db.create_dup_table(&AddressHistoryTable);
-        Self
+        db.create_regular_table(&TokenScoreTable);
+        db.create_dup_table(&BlockTokenSnapshotTable);
+        db.create_dup_table(&TokenHistoryTable);
+        db.create_regular_table(&CheckpointTable);
+        db.create_regular_table(&ScanProgressTable);
+        db.create_regular_table(&MetaTable);
+        db.create_regular_table(&BlockPriceTable);
+        Self {
+            policy,
+            abort: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Stamps the on-disk format version and active policy tag so later runs
+    /// can detect an incompatible format or a different policy. Called once a
+    /// fresh database is initialized.
+    pub fn write_meta(&self, txn: &mut MdbxWriteTransaction) {
+        txn.put(&MetaTable, &META_FORMAT_KEY, &SCORE_FORMAT_VERSION);
+        txn.put(&MetaTable, &META_POLICY_KEY, &self.policy.tag());
+    }
+
+    /// Verifies that an existing database is compatible with this run: the
+    /// recorded format version must be understood and the recorded policy must
+    /// match the one selected now, so a database is never mixed across
+    /// policies. A database with no recorded metadata predates versioning and
+    /// is accepted as the scalar v0 format under its original policy.
+    pub fn verify_meta(&self, txn: &MdbxReadTransaction) -> Result<(), ScoreDbError> {
+        if let Some(version) = txn.get(&MetaTable, &META_FORMAT_KEY) {
+            if version > SCORE_FORMAT_VERSION {
+                return Err(ScoreDbError::StateCorrupt(format!(
+                    "database format v{version} is newer than supported v{SCORE_FORMAT_VERSION}"
+                )));
+            }
+        }
+        if let Some(tag) = txn.get(&MetaTable, &META_POLICY_KEY) {
+            if tag != self.policy.tag() {
+                let stored = PolicyKind::from_tag(tag)
+                    .map(|p| format!("{p:?}"))
+                    .unwrap_or_else(|| format!("tag {tag}"));
+                return Err(ScoreDbError::StateCorrupt(format!(
+                    "database was built with the {stored} policy; refusing to mix with {:?}",
+                    self.policy
+                )));
+            }
+        }
+        Ok(())
     }
 
-    /// Initializes the TC contracts as fully dirty.
-    pub async fn init_tc(
+    /// A handle to this scan's abort flag. A caller can install it in a
+    /// `ctrl_c` handler; setting it makes the next [`Self::flush_cache`] commit
+    /// its block and report the abort so the scan loop can stop cleanly.
+    pub fn abort_handle(&self) -> Arc<AtomicBool> {
+        Arc::clone(&self.abort)
+    }
+
+    /// Initializes the spec's taint-source addresses as fully dirty.
+    pub async fn init_dirty_sources(
         &self,
         cache: &Cache,
         provider: &RootProvider<PubSubFrontend>,
+        sources: &[Address],
         block_number: u64,
-    ) -> Result<()> {
-        for address in TC_ETH_ADDRESS.iter() {
+    ) -> Result<(), ScoreDbError> {
+        for address in sources.iter() {
             let balance = provider
                 .get_balance(*address)
                 .block_id(block_number.into())
-                .await?;
+                .await
+                .map_err(|e| ScoreDbError::Provider(e.into()))?;
             cache.insert_data(AddressKey::new(*address), Score::new_dirty(balance));
         }
         Ok(())
@@ -65,6 +183,76 @@ impl ScoreDb {
         txn.clear_table(&AddressScoreTable);
         txn.clear_table(&BlockSnapshotTable);
         txn.clear_table(&AddressHistoryTable);
+        txn.clear_table(&TokenScoreTable);
+        txn.clear_table(&BlockTokenSnapshotTable);
+        txn.clear_table(&TokenHistoryTable);
+        txn.clear_table(&CheckpointTable);
+        txn.clear_table(&ScanProgressTable);
+        txn.clear_table(&MetaTable);
+        txn.clear_table(&BlockPriceTable);
+    }
+
+    /// Records the USD/ETH price (scaled by `10^18`) for a single block.
+    pub fn record_block_price(
+        &self,
+        txn: &mut MdbxWriteTransaction,
+        block_number: u64,
+        usd_per_eth_scaled: U256,
+    ) {
+        txn.put(
+            &BlockPriceTable,
+            &block_number,
+            &UsdPrice::new(usd_per_eth_scaled),
+        );
+    }
+
+    /// Populates [`BlockPriceTable`] for `[from_block, to_block]` from an
+    /// injected [`PriceSource`], skipping blocks the source has no price for.
+    pub fn populate_block_prices(
+        &self,
+        txn: &mut MdbxWriteTransaction,
+        source: &impl PriceSource,
+        from_block: u64,
+        to_block: u64,
+    ) {
+        for block_number in from_block..=to_block {
+            if let Some(price) = source.price_at_block(block_number) {
+                self.record_block_price(txn, block_number, price);
+            }
+        }
+    }
+
+    /// Loads the recorded prices into an in-memory index so repeated
+    /// nearest-block lookups during an export avoid a cursor seek per row.
+    fn price_index(&self, txn: &MdbxReadTransaction) -> BTreeMap<u64, U256> {
+        let cursor = ReadTransaction::cursor(txn, &BlockPriceTable);
+        cursor
+            .into_iter_start()
+            .map(|(block, price)| (block, price.usd_per_eth_scaled))
+            .collect()
+    }
+
+    /// The covered block range and phase of the scan, if one has begun.
+    pub fn get_scan_progress(&self, txn: &MdbxReadTransaction) -> Option<ScanProgress> {
+        txn.get(&ScanProgressTable, &SCAN_PROGRESS_KEY)
+    }
+
+    /// Persists the scan-progress envelope, replacing any previous record.
+    pub fn write_scan_progress(&self, txn: &mut MdbxWriteTransaction, progress: &ScanProgress) {
+        txn.put(&ScanProgressTable, &SCAN_PROGRESS_KEY, progress);
+    }
+
+    /// Height of the most recent complete checkpoint, if one was written.
+    pub fn get_checkpoint(&self, txn: &MdbxReadTransaction) -> Option<u64> {
+        txn.get(&CheckpointTable, &CHECKPOINT_KEY)
+    }
+
+    /// Atomically records a checkpoint at `block_number`, replacing any
+    /// previous checkpoint. The address scores themselves are already persisted
+    /// by [`Self::flush_cache`]; the checkpoint just marks the point up to which
+    /// that work is known complete so a crash can rewind cleanly to it.
+    pub fn write_checkpoint(&self, txn: &mut MdbxWriteTransaction, block_number: u64) {
+        txn.put(&CheckpointTable, &CHECKPOINT_KEY, &block_number);
     }
 
     /// Gets the block number from the last time running
@@ -80,8 +268,21 @@ impl ScoreDb {
         provider: &RootProvider<PubSubFrontend>,
         cache: &Cache,
         block_number: u64,
-    ) -> Result<()> {
-        for (address, score) in cache.drain_data() {
+    ) -> Result<bool> {
+        // Read the abort request once up front: whatever we were handed for this
+        // block is still committed below, so a stop never loses the work already
+        // done, but no further block is started.
+        let aborted = self.abort.load(Ordering::Relaxed);
+
+        // Persist the addresses evicted under memory pressure first, then
+        // overlay the still-resident hot set so each address is written once
+        // with its most recent score for this block.
+        let mut scores = BTreeMap::new();
+        for (address, score) in cache.drain_evicted_data() {
+            scores.insert(address, score);
+        }
+        scores.extend(cache.drain_data());
+        for (address, score) in scores {
             // // Check whether the calculated balance of the address at the given block number is correct or not
             // let balance = provider
             //     .get_balance(*address)
@@ -130,9 +331,266 @@ impl ScoreDb {
                 }
             }
         }
+
+        // Persist token balances that have changed during this block, mirroring
+        // the ETH provenance so a reorg can unwind them. `TokenScoreTable` holds
+        // only the latest dirty balance per `(account, token)` pair, while the
+        // snapshot/history tables record every change involving an unclean pair.
+        for (key, score) in cache.drain_token_data() {
+            match txn.get(&TokenScoreTable, &key) {
+                Some(_) => {
+                    if score.is_dirty() {
+                        txn.put(&TokenScoreTable, &key, &score);
+                    } else {
+                        txn.remove(&TokenScoreTable, &key);
+                    }
+                    txn.put(&TokenHistoryTable, &key, &block_number);
+                    txn.put(
+                        &BlockTokenSnapshotTable,
+                        &block_number,
+                        &IndexedValue::new(key.clone(), score),
+                    );
+                }
+                None => {
+                    if score.is_dirty() {
+                        txn.put(&TokenScoreTable, &key, &score);
+                        txn.put(&TokenHistoryTable, &key, &block_number);
+                        txn.put(
+                            &BlockTokenSnapshotTable,
+                            &block_number,
+                            &IndexedValue::new(key.clone(), score),
+                        );
+                    }
+                }
+            }
+        }
+
+        // Extend the covered range to this block so a restart can resume from
+        // the right place; flag the phase as aborted when a stop was requested.
+        let status = if aborted {
+            ScanStatus::Aborted
+        } else {
+            ScanStatus::Forward
+        };
+        let progress = match txn.get(&ScanProgressTable, &SCAN_PROGRESS_KEY) {
+            Some(mut progress) => {
+                progress.cover(block_number, status);
+                progress
+            }
+            None => {
+                let mut progress = ScanProgress::started(block_number);
+                progress.status = status;
+                progress
+            }
+        };
+        txn.put(&ScanProgressTable, &SCAN_PROGRESS_KEY, &progress);
+
+        Ok(aborted)
+    }
+
+    /// Undoes every score contribution attributed to `block_number`, reverting
+    /// each ETH and token balance it touched to the score it carried at its
+    /// closest earlier change (or dropping it entirely if this block first made
+    /// it unclean).
+    ///
+    /// The per-block provenance kept in [`BlockSnapshotTable`]/
+    /// [`AddressHistoryTable`] (ETH) and [`BlockTokenSnapshotTable`]/
+    /// [`TokenHistoryTable`] (tokens) is exactly what makes this reversible: the
+    /// snapshot rows for the orphaned block are removed and the history entries
+    /// pointing at it are unlinked, so re-applying the canonical block yields
+    /// the same tables as if the orphaned block had never been seen. A
+    /// self-destruct needs no separate provenance — it is flushed as a zeroed
+    /// score with a [`BlockSnapshotTable`] row like any other change, so it
+    /// reverts through the same path. This is the reorg-rollback counterpart of
+    /// [`Self::flush_cache`].
+    pub fn undo_block(
+        &self,
+        txn: &mut MdbxWriteTransaction,
+        block_number: u64,
+    ) -> Result<(), ScoreDbError> {
+        // Read phase: gather the addresses changed in this block and the score
+        // each should revert to, before mutating anything under the cursors.
+        let changed: Vec<AddressKey> = {
+            let dup_cursor_block_snap = ReadTransaction::dup_cursor(txn, &BlockSnapshotTable);
+            dup_cursor_block_snap
+                .into_iter_dup_of(&block_number)
+                .map(|(_, v)| AddressKey::new(*v.index))
+                .collect()
+        };
+
+        let mut restores: Vec<(AddressKey, Option<Score>)> = Vec::with_capacity(changed.len());
+        {
+            let mut dup_cursor_block_snap = ReadTransaction::dup_cursor(txn, &BlockSnapshotTable);
+            for address in changed.iter() {
+                // The closest earlier block in which this address changed, if any.
+                let dup_cursor_address_history =
+                    ReadTransaction::dup_cursor(txn, &AddressHistoryTable);
+                let previous_block = dup_cursor_address_history
+                    .into_iter_dup_of(address)
+                    .map(|(_, v)| v)
+                    .filter(|block| *block < block_number)
+                    .last();
+                let restore = match previous_block {
+                    Some(previous_block) => Some(
+                        dup_cursor_block_snap
+                            .set_subkey(&previous_block, address)
+                            .ok_or_else(|| ScoreDbError::SnapshotMissing {
+                                block: previous_block,
+                                address: address.to_string(),
+                            })?
+                            .value,
+                    ),
+                    None => None,
+                };
+                restores.push((address.clone(), restore));
+            }
+        }
+
+        // Write phase: unlink the orphaned block and restore the prior scores.
+        for (address, restore) in restores {
+            txn.remove_item(&AddressHistoryTable, &address, &block_number);
+            match restore {
+                // The address was unclean before this block; restore that score.
+                Some(score) if score.is_dirty() => {
+                    txn.put(&AddressScoreTable, &address, &score);
+                }
+                // Either it was clean before, or this block first tainted it;
+                // `AddressScoreTable` only holds unclean addresses, so drop it.
+                _ => {
+                    txn.remove(&AddressScoreTable, &address);
+                }
+            }
+        }
+        // Drop every snapshot row recorded for the orphaned block.
+        txn.remove(&BlockSnapshotTable, &block_number);
+
+        // Revert the token balances changed by the orphaned block, mirroring
+        // the ETH rollback above.
+        let tokens_changed: Vec<TokenKey> = {
+            let dup_cursor_token_snap = ReadTransaction::dup_cursor(txn, &BlockTokenSnapshotTable);
+            dup_cursor_token_snap
+                .into_iter_dup_of(&block_number)
+                .map(|(_, v)| v.index.clone())
+                .collect()
+        };
+        let mut token_restores: Vec<(TokenKey, Option<Score>)> =
+            Vec::with_capacity(tokens_changed.len());
+        {
+            let mut dup_cursor_token_snap =
+                ReadTransaction::dup_cursor(txn, &BlockTokenSnapshotTable);
+            for key in tokens_changed.iter() {
+                let dup_cursor_token_history = ReadTransaction::dup_cursor(txn, &TokenHistoryTable);
+                let previous_block = dup_cursor_token_history
+                    .into_iter_dup_of(key)
+                    .map(|(_, v)| v)
+                    .filter(|block| *block < block_number)
+                    .last();
+                let restore = match previous_block {
+                    Some(previous_block) => Some(
+                        dup_cursor_token_snap
+                            .set_subkey(&previous_block, key)
+                            .ok_or_else(|| ScoreDbError::SnapshotMissing {
+                                block: previous_block,
+                                address: format!("{}:{}", key.account(), key.token()),
+                            })?
+                            .value,
+                    ),
+                    None => None,
+                };
+                token_restores.push((key.clone(), restore));
+            }
+        }
+        for (key, restore) in token_restores {
+            txn.remove_item(&TokenHistoryTable, &key, &block_number);
+            match restore {
+                Some(score) if score.is_dirty() => {
+                    txn.put(&TokenScoreTable, &key, &score);
+                }
+                _ => {
+                    txn.remove(&TokenScoreTable, &key);
+                }
+            }
+        }
+        txn.remove(&BlockTokenSnapshotTable, &block_number);
+
         Ok(())
     }
 
+    /// Backfills a single historical `block_number` that predates the
+    /// forward-built range, merging into the existing tables without touching
+    /// the already-computed higher blocks.
+    ///
+    /// For every currently-unclean address that has no snapshot at this block
+    /// yet, the balance at this height is read from the provider and valued
+    /// under the selected taint policy (the same rule the forward scan applies),
+    /// then recorded in the snapshot and history tables so a database is never
+    /// mixed across models. Addresses that already carry a snapshot for this block
+    /// are left untouched, so a backfill interrupted partway can re-run this
+    /// block idempotently. Returns `true` if an abort was requested, in which
+    /// case the caller commits this block and stops.
+    pub async fn backfill_block<'a>(
+        &self,
+        txn: &mut MdbxWriteTransaction<'a>,
+        provider: &RootProvider<PubSubFrontend>,
+        block_number: u64,
+    ) -> Result<bool, ScoreDbError> {
+        let aborted = self.abort.load(Ordering::Relaxed);
+
+        // Snapshot the current unclean set before issuing any provider query,
+        // so the cursor is not held across an await.
+        let dirty_addresses: Vec<(AddressKey, Score)> = {
+            let cursor = ReadTransaction::cursor(txn, &AddressScoreTable);
+            cursor.into_iter_start().collect()
+        };
+
+        for (address, current) in dirty_addresses {
+            // Never overwrite a snapshot that already exists for this block.
+            let mut dup_cursor_block_snap = ReadTransaction::dup_cursor(txn, &BlockSnapshotTable);
+            if dup_cursor_block_snap
+                .set_subkey(&block_number, &address)
+                .is_some()
+            {
+                continue;
+            }
+            let balance = provider
+                .get_balance(*address)
+                .block_id(block_number.into())
+                .await
+                .map_err(|e| ScoreDbError::Provider(e.into()))?;
+            if balance.is_zero() {
+                continue;
+            }
+            let score = self.policy.split_transfer(balance, &current)?;
+            txn.put(&AddressHistoryTable, &address, &block_number);
+            txn.put(
+                &BlockSnapshotTable,
+                &block_number,
+                &IndexedValue::new(address.clone(), score),
+            );
+        }
+
+        // Lower the covered range to include this block.
+        let status = if aborted {
+            ScanStatus::Aborted
+        } else {
+            ScanStatus::Backfill
+        };
+        let progress = match txn.get(&ScanProgressTable, &SCAN_PROGRESS_KEY) {
+            Some(mut progress) => {
+                progress.cover(block_number, status);
+                progress
+            }
+            None => {
+                let mut progress = ScanProgress::started(block_number);
+                progress.status = status;
+                progress
+            }
+        };
+        txn.put(&ScanProgressTable, &SCAN_PROGRESS_KEY, &progress);
+
+        Ok(aborted)
+    }
+
     /// Tries to get a previous score from cache and database.
     fn get_score(
         &self,
@@ -154,12 +612,13 @@ impl ScoreDb {
         block_number: u64,
         address: &AddressKey,
         transfer_value: U256,
-    ) -> Result<Score> {
+    ) -> Result<Score, ScoreDbError> {
         // Retrieve sender's current score and calculate transfer ETH's score.
         let (sender_score, transfer_score) = match self.get_score(txn, cache, address) {
             Some(score) => {
-                // We already have a previous score, so we calculate the transfer score based on this
-                let transfer_score = Score::with_same_uncleanliness_ceil(transfer_value, &score);
+                // We already have a previous score, so we split the transfer's
+                // dirtiness off it according to the selected taint policy.
+                let transfer_score = self.policy.split_transfer(transfer_value, &score)?;
                 (score, transfer_score)
             }
             None => {
@@ -167,21 +626,19 @@ impl ScoreDb {
                 let balance = provider
                     .get_balance(**address)
                     .block_id((block_number - 1).into())
-                    .await?;
+                    .await
+                    .map_err(|e| ScoreDbError::Provider(e.into()))?;
                 let score = Score::new_clean(balance);
                 let transfer_score = Score::new_clean(transfer_value);
                 (score, transfer_score)
             }
         };
-        // Panics if `transfer value > the balance sender holds``
-        assert!(
-            transfer_score.balance <= sender_score.balance,
-            "Cannot send more than available ({} <= {})",
-            transfer_score.balance,
-            sender_score.balance
-        );
-        // Cache the score of every occurred address in the block.
-        let score_post = sender_score - transfer_score;
+        // Debit the sender, clamping its residual dirty at zero so a
+        // non-proportional policy (e.g. poison) that attributes more taint to
+        // the transfer than the sender itself carried cannot underflow
+        // `U256::sub`. A transfer larger than the balance still surfaces as a
+        // divergence between the trace and the accounting.
+        let score_post = debit(sender_score, &transfer_score)?;
         cache.insert_data(address.clone(), score_post);
         Ok(transfer_score)
     }
@@ -195,7 +652,7 @@ impl ScoreDb {
         block_number: u64,
         address: &AddressKey,
         mut transfer_score: Score,
-    ) -> Result<()> {
+    ) -> Result<(), ScoreDbError> {
         // If depositing into a TC contract, mark the full balance as dirty.
         if TC_ETH_ADDRESS.iter().any(|tc_addr| *tc_addr == **address) {
             transfer_score = transfer_score.as_dirty();
@@ -210,7 +667,8 @@ impl ScoreDb {
                 let balance = provider
                     .get_balance(**address)
                     .block_id((block_number - 1).into())
-                    .await?;
+                    .await
+                    .map_err(|e| ScoreDbError::Provider(e.into()))?;
                 Score::new_clean(balance)
             }
         };
@@ -236,7 +694,7 @@ impl ScoreDb {
         recipient_address: &AddressKey,
         sender_value: U256,
         recipient_value: Option<U256>,
-    ) -> Result<()> {
+    ) -> Result<(), ScoreDbError> {
         // Update sender, otherwise assume clean amount.
         let mut transfer_score = if let Some(sender_address) = sender_address {
             self.update_sender_state(
@@ -254,13 +712,13 @@ impl ScoreDb {
 
         // Recalculate transfer value on recipient side if `recipient_value` is given.
         if let Some(recipient_value) = recipient_value {
-            assert!(
-                recipient_value <= sender_value,
-                "Cannot receive more than sent ({} <= {})",
-                recipient_value,
-                sender_value
-            );
-            transfer_score = Score::with_same_uncleanliness_ceil(recipient_value, &transfer_score);
+            if recipient_value > sender_value {
+                return Err(ScoreDbError::BalanceUnderflow {
+                    balance: sender_value,
+                    transfer: recipient_value,
+                });
+            }
+            transfer_score = self.policy.split_transfer(recipient_value, &transfer_score)?;
         }
 
         // Update recipient.
@@ -277,6 +735,148 @@ impl ScoreDb {
         Ok(())
     }
 
+    /// Tries to get a previous token score from cache and database.
+    fn get_token_score(
+        &self,
+        txn: &MdbxReadTransaction,
+        cache: &Cache,
+        key: &TokenKey,
+    ) -> Option<Score> {
+        cache
+            .get_token_data(key)
+            .or_else(|| txn.get(&TokenScoreTable, key))
+    }
+
+    /// Queries the on-chain `balanceOf` of a token holder at `block_number`.
+    async fn token_balance_of(
+        provider: &RootProvider<PubSubFrontend>,
+        token: Address,
+        account: Address,
+        block_number: u64,
+    ) -> Result<U256, ScoreDbError> {
+        let contract = IERC20::new(token, provider);
+        let balance = contract
+            .balanceOf(account)
+            .block(block_number.into())
+            .call()
+            .await
+            .map_err(|e| ScoreDbError::Provider(e.into()))?
+            ._0;
+        Ok(balance)
+    }
+
+    /// Subtracts a token transfer from the sender and returns the score to be
+    /// added to the recipient, mirroring [`Self::update_sender_state`] for ETH.
+    async fn update_token_sender_state<'a>(
+        &self,
+        txn: &mut MdbxWriteTransaction<'a>,
+        cache: &Cache,
+        provider: &RootProvider<PubSubFrontend>,
+        block_number: u64,
+        key: &TokenKey,
+        transfer_value: U256,
+    ) -> Result<Score, ScoreDbError> {
+        let (sender_score, transfer_score) = match self.get_token_score(txn, cache, key) {
+            Some(score) => {
+                let transfer_score = self.policy.split_transfer(transfer_value, &score)?;
+                (score, transfer_score)
+            }
+            None => {
+                let balance =
+                    Self::token_balance_of(provider, key.token(), key.account(), block_number - 1)
+                        .await?;
+                (Score::new_clean(balance), Score::new_clean(transfer_value))
+            }
+        };
+        // Mirror the ETH path: a transfer can never move more than the sender
+        // holds, and the residual dirty amount is clamped so a policy that
+        // taints more than the sender carried cannot underflow.
+        cache.insert_token_data(key.clone(), debit(sender_score, &transfer_score)?);
+        Ok(transfer_score)
+    }
+
+    /// Adds a token transfer to the recipient, mirroring
+    /// [`Self::update_recipient_state`] for ETH.
+    async fn update_token_recipient_state<'a>(
+        &self,
+        txn: &mut MdbxWriteTransaction<'a>,
+        cache: &Cache,
+        provider: &RootProvider<PubSubFrontend>,
+        block_number: u64,
+        key: &TokenKey,
+        mut transfer_score: Score,
+    ) -> Result<(), ScoreDbError> {
+        // Tokens received by a sanctioned address originate taint, reusing the
+        // mark-dirty-on-receipt mechanism of the native-ETH sink rule in
+        // [`Self::update_recipient_state`] but over the sanctioned-entity set
+        // rather than the TC pools: funds controlled by a sanctioned entity are
+        // dirty and carry that taint onward when the entity later routes them
+        // through a token.
+        if is_token_sink(&key.account()) {
+            transfer_score = transfer_score.as_dirty();
+        }
+
+        let recipient_score = match self.get_token_score(txn, cache, key) {
+            Some(score) => score,
+            None => {
+                let balance =
+                    Self::token_balance_of(provider, key.token(), key.account(), block_number - 1)
+                        .await?;
+                Score::new_clean(balance)
+            }
+        };
+        cache.insert_token_data(key.clone(), recipient_score + transfer_score);
+        Ok(())
+    }
+
+    /// Records an ERC-20/ERC-721 `Transfer` decoded from a receipt log, running
+    /// the same policy-driven taint accounting used for native ETH on the
+    /// per-`(account, token)` balances.
+    pub async fn record_token_transfer<'a>(
+        &self,
+        txn: &mut MdbxWriteTransaction<'a>,
+        cache: &Cache,
+        provider: &RootProvider<PubSubFrontend>,
+        block_number: u64,
+        token: Address,
+        from: Address,
+        to: Address,
+        value: U256,
+    ) -> Result<(), ScoreDbError> {
+        // A zero-value transfer moves nothing. Below, the mint/burn
+        // pseudo-address (zero) carries no taint of its own.
+        if value == U256::ZERO {
+            return Ok(());
+        }
+        let transfer_score = if from != Address::ZERO {
+            let sender_key = TokenKey::new(from, token);
+            self.update_token_sender_state(
+                txn,
+                cache,
+                provider,
+                block_number,
+                &sender_key,
+                value,
+            )
+            .await?
+        } else {
+            Score::new_clean(value)
+        };
+        if to != Address::ZERO {
+            let recipient_key = TokenKey::new(to, token);
+            self.update_token_recipient_state(
+                txn,
+                cache,
+                provider,
+                block_number,
+                &recipient_key,
+                transfer_score,
+            )
+            .await?;
+        }
+        Ok(())
+    }
+
     /// Print the number of records in each database
     pub fn print_record_number(&self, txn: &MdbxReadTransaction) {
         let cursor_address_score = ReadTransaction::cursor(txn, &AddressScoreTable);
@@ -304,21 +904,34 @@ impl ScoreDb {
         &self,
         txn: &MdbxReadTransaction<'a>,
         client: &PostgresClient,
-    ) -> Result<()> {
+    ) -> Result<(), ScoreDbError> {
+        ensure_addresses_table(client).await?;
+        client
+            .execute(
+                "
+                CREATE TABLE IF NOT EXISTS latest_address_score_bybit (
+                    address_id BIGINT PRIMARY KEY REFERENCES addresses (address_id),
+                    total_balance NUMERIC,
+                    dirty_amount NUMERIC
+                );",
+                &[],
+            )
+            .await?;
+
+        let mut address_ids: BTreeMap<Address, i64> = BTreeMap::new();
         let cursor = ReadTransaction::cursor(txn, &AddressScoreTable);
         for (address, score) in cursor.into_iter_start() {
+            let address_id = intern_address(client, &mut address_ids, *address).await?;
             client
                 .execute(
                     "
-                INSERT INTO latest_address_score_bybit (address, total_balance, dirty_amount)
+                INSERT INTO latest_address_score_bybit (address_id, total_balance, dirty_amount)
                 VALUES ($1, $2, $3)
-                ON CONFLICT (address) DO NOTHING;",
+                ON CONFLICT (address_id) DO NOTHING;",
                     &[
-                        &address.to_string(),
-                        &Decimal::from_str_exact(&score.balance.to_string())
-                            .expect("Invalid total balance"),
-                        &Decimal::from_str_exact(&score.dirty_amount.to_string())
-                            .expect("Invalid dirty amount"),
+                        &address_id,
+                        &decimal(&score.balance)?,
+                        &decimal(&score.dirty_amount)?,
                     ],
                 )
                 .await?;
@@ -333,13 +946,23 @@ impl ScoreDb {
         address: &AddressKey,
         from_block: u64,
         to_block: u64,
-    ) -> Result<()> {
+    ) -> Result<(), ScoreDbError> {
         let file = File::create(format!(
             "./output/output_historical_{}_between_{}_and_{}.csv",
             **address, from_block, to_block
-        ))?;
+        ))
+        .map_err(|e| ScoreDbError::Serialize(e.to_string()))?;
         let mut wtr = Writer::from_writer(file);
-        wtr.serialize(("address", "block_number", "total_balance", "dirty_amount"))?;
+        wtr.serialize((
+            "address",
+            "block_number",
+            "total_balance",
+            "dirty_amount",
+            "total_balance_usd",
+            "dirty_amount_usd",
+        ))
+        .map_err(|e| ScoreDbError::Serialize(e.to_string()))?;
+        let prices = self.price_index(txn);
         let dup_cursor_address_history = ReadTransaction::dup_cursor(txn, &AddressHistoryTable);
         let mut dup_cursor_block_snap = ReadTransaction::dup_cursor(txn, &BlockSnapshotTable);
         let block_entire_history: Vec<u64> = dup_cursor_address_history
@@ -357,13 +980,28 @@ impl ScoreDb {
         };
 
         for block_number in block_entire_history[start_idx..end_idx].iter() {
+            // The history table says this address changed at `block_number`, so
+            // the matching snapshot must exist; its absence is corruption.
             let score = dup_cursor_block_snap
                 .set_subkey(block_number, address)
-                .unwrap()
+                .ok_or_else(|| ScoreDbError::SnapshotMissing {
+                    block: *block_number,
+                    address: address.to_string(),
+                })?
                 .value;
-            wtr.serialize((**address, block_number, score.balance, score.dirty_amount))?;
+            let price = price_at(&prices, *block_number).unwrap_or(U256::ZERO);
+            wtr.serialize((
+                **address,
+                block_number,
+                score.balance,
+                score.dirty_amount,
+                fiat_value(&score.balance, &price)?,
+                fiat_value(&score.dirty_amount, &price)?,
+            ))
+            .map_err(|e| ScoreDbError::Serialize(e.to_string()))?;
         }
-        wtr.flush()?;
+        wtr.flush()
+            .map_err(|e| ScoreDbError::Serialize(e.to_string()))?;
         Ok(())
     }
 
@@ -371,10 +1009,12 @@ impl ScoreDb {
     pub fn export_historical_amount_of_tainted_addresses(
         &self,
         txn: &MdbxReadTransaction,
-    ) -> Result<()> {
-        let file = File::create("./output/output_historical_amount_of_tainted_addresses.csv")?;
+    ) -> Result<(), ScoreDbError> {
+        let file = File::create("./output/output_historical_amount_of_tainted_addresses.csv")
+            .map_err(|e| ScoreDbError::Serialize(e.to_string()))?;
         let mut wtr = Writer::from_writer(file);
-        wtr.serialize(("block_number", "address_amount"))?;
+        wtr.serialize(("block_number", "address_amount"))
+            .map_err(|e| ScoreDbError::Serialize(e.to_string()))?;
 
         let mut prev_key = None;
         let mut address_set: BTreeSet<Address> = BTreeSet::new();
@@ -382,7 +1022,8 @@ impl ScoreDb {
         for (k, v) in dup_cursor_block_snap.into_iter_start() {
             // Write the length of the address_set when the key changes
             if prev_key.is_some() && prev_key != Some(k) {
-                wtr.serialize((prev_key.unwrap(), address_set.len()))?;
+                wtr.serialize((prev_key.unwrap(), address_set.len()))
+                    .map_err(|e| ScoreDbError::Serialize(e.to_string()))?;
             }
             address_set.insert(*v.index);
             prev_key = Some(k);
@@ -390,9 +1031,11 @@ impl ScoreDb {
 
         // Export the last block
         if let Some(last_key) = prev_key {
-            wtr.serialize((last_key, address_set.len()))?;
+            wtr.serialize((last_key, address_set.len()))
+                .map_err(|e| ScoreDbError::Serialize(e.to_string()))?;
         }
-        wtr.flush()?;
+        wtr.flush()
+            .map_err(|e| ScoreDbError::Serialize(e.to_string()))?;
 
         Ok(())
     }
@@ -402,12 +1045,13 @@ impl ScoreDb {
         &self,
         txn: &MdbxReadTransaction,
         block_number: u64,
-    ) -> Result<BTreeMap<Address, Score>> {
+    ) -> Result<BTreeMap<Address, Score>, ScoreDbError> {
         let mut address_score: BTreeMap<Address, Score> = BTreeMap::new();
         let file = File::create(format!(
             "./output/output_tainted_addresses_until_{}.csv",
             block_number
-        ))?;
+        ))
+        .map_err(|e| ScoreDbError::Serialize(e.to_string()))?;
         let dup_cursor_block_snap = ReadTransaction::dup_cursor(txn, &BlockSnapshotTable);
         for (k, v) in dup_cursor_block_snap.into_iter_start() {
             if k > block_number {
@@ -417,13 +1061,16 @@ impl ScoreDb {
             }
         }
         let mut wtr = Writer::from_writer(file);
-        wtr.serialize(("address", "total_balance", "dirty_amount"))?;
+        wtr.serialize(("address", "total_balance", "dirty_amount"))
+            .map_err(|e| ScoreDbError::Serialize(e.to_string()))?;
         for (address, score) in address_score.iter() {
             if score.is_dirty() {
-                wtr.serialize((*address, score.balance, score.dirty_amount))?;
+                wtr.serialize((*address, score.balance, score.dirty_amount))
+                    .map_err(|e| ScoreDbError::Serialize(e.to_string()))?;
             }
         }
-        wtr.flush()?;
+        wtr.flush()
+            .map_err(|e| ScoreDbError::Serialize(e.to_string()))?;
         Ok(address_score)
     }
 
@@ -434,31 +1081,90 @@ impl ScoreDb {
         conn: &PooledConnection<'a, PostgresConnectionManager<NoTls>>,
         from_block: u64,
         to_block: u64,
-    ) -> Result<()> {
+    ) -> Result<(), ScoreDbError> {
+        let client: &PostgresClient = conn;
+        ensure_addresses_table(client).await?;
+        client
+            .execute(
+                "
+                CREATE TABLE IF NOT EXISTS block_snapshot (
+                    block_number BIGINT,
+                    address_id BIGINT REFERENCES addresses (address_id),
+                    total_balance NUMERIC,
+                    dirty_amount NUMERIC,
+                    total_balance_usd NUMERIC,
+                    dirty_amount_usd NUMERIC,
+                    PRIMARY KEY (block_number, address_id)
+                );",
+                &[],
+            )
+            .await?;
+        client
+            .execute(
+                "
+                CREATE TABLE IF NOT EXISTS blocks (
+                    block_number BIGINT PRIMARY KEY,
+                    tainted_address_count INT,
+                    total_dirty_amount NUMERIC
+                );",
+                &[],
+            )
+            .await?;
+
+        let mut address_ids: BTreeMap<Address, i64> = BTreeMap::new();
+        let prices = self.price_index(txn);
+        // Aggregates for the `blocks` summary row of the block currently being
+        // streamed. The cursor walks `(block_number, address)` in order, so a
+        // change of block number flushes the completed block's summary.
+        let mut summary_block: Option<u64> = None;
+        let mut tainted_count: i32 = 0;
+        let mut total_dirty = U256::ZERO;
+
         let dup_cursor_block_snap = ReadTransaction::dup_cursor(txn, &BlockSnapshotTable);
         for (block_number, address_with_score) in dup_cursor_block_snap.into_iter_from(&from_block)
         {
             if block_number > to_block {
                 break;
             }
-            let address = address_with_score.index;
+            if summary_block != Some(block_number) {
+                if let Some(prev) = summary_block {
+                    write_block_summary(client, prev, tainted_count, &total_dirty).await?;
+                }
+                summary_block = Some(block_number);
+                tainted_count = 0;
+                total_dirty = U256::ZERO;
+            }
+
+            let address = *address_with_score.index;
             let total_balance = address_with_score.value.balance;
             let dirty_amount = address_with_score.value.dirty_amount;
-            conn.execute(
-                "
-                INSERT INTO block_snapshot (block_number, address, total_balance, dirty_amount)
-                VALUES ($1, $2, $3, $4)
-                ON CONFLICT (block_number, address) DO NOTHING;",
-                &[
-                    &(block_number as i64),
-                    &address.to_string(),
-                    &Decimal::from_str_exact(&total_balance.to_string())
-                        .expect("Invalid total balance"),
-                    &Decimal::from_str_exact(&dirty_amount.to_string())
-                        .expect("Invalid dirty amount"),
-                ],
-            )
-            .await?;
+            if address_with_score.value.is_dirty() {
+                tainted_count += 1;
+                total_dirty += dirty_amount;
+            }
+            let address_id = intern_address(client, &mut address_ids, address).await?;
+            let price = price_at(&prices, block_number).unwrap_or(U256::ZERO);
+            client
+                .execute(
+                    "
+                INSERT INTO block_snapshot
+                    (block_number, address_id, total_balance, dirty_amount,
+                     total_balance_usd, dirty_amount_usd)
+                VALUES ($1, $2, $3, $4, $5, $6)
+                ON CONFLICT (block_number, address_id) DO NOTHING;",
+                    &[
+                        &(block_number as i64),
+                        &address_id,
+                        &decimal(&total_balance)?,
+                        &decimal(&dirty_amount)?,
+                        &fiat_value(&total_balance, &price)?,
+                        &fiat_value(&dirty_amount, &price)?,
+                    ],
+                )
+                .await?;
+        }
+        if let Some(prev) = summary_block {
+            write_block_summary(client, prev, tainted_count, &total_dirty).await?;
         }
         Ok(())
     }
@@ -468,14 +1174,15 @@ impl ScoreDb {
         txn: &MdbxReadTransaction<'a>,
         provider: &RootProvider<PubSubFrontend>,
         address: &AddressKey,
-    ) -> Result<Score> {
+    ) -> Result<Score, ScoreDbError> {
         match txn.get(&AddressScoreTable, address) {
             Some(score) => Ok(score),
             None => {
                 let balance = provider
                     .get_balance(**address)
                     .block_id(20305757.into())
-                    .await?;
+                    .await
+                    .map_err(|e| ScoreDbError::Provider(e.into()))?;
                 Ok(Score::new_clean(balance))
             }
         }
@@ -488,7 +1195,7 @@ impl ScoreDb {
         provider: &RootProvider<PubSubFrontend>,
         address: &AddressKey,
         block_number: u64,
-    ) -> Result<Score> {
+    ) -> Result<Score, ScoreDbError> {
         let dup_cursor_address_history = ReadTransaction::dup_cursor(txn, &AddressHistoryTable);
         let mut dup_cursor_block_snap = ReadTransaction::dup_cursor(txn, &BlockSnapshotTable);
         let block_entire_history: Vec<u64> = dup_cursor_address_history
@@ -518,7 +1225,8 @@ impl ScoreDb {
                     let balance = provider
                         .get_balance(**address)
                         .block_id(block_number.into())
-                        .await?;
+                        .await
+                        .map_err(|e| ScoreDbError::Provider(e.into()))?;
                     Score::new_clean(balance)
                 }
             }
@@ -526,7 +1234,8 @@ impl ScoreDb {
                 let balance = provider
                     .get_balance(**address)
                     .block_id(block_number.into())
-                    .await?;
+                    .await
+                    .map_err(|e| ScoreDbError::Provider(e.into()))?;
                 Score::new_clean(balance)
             }
         };
@@ -538,7 +1247,7 @@ impl ScoreDb {
         &self,
         txn: &MdbxReadTransaction,
         address: &AddressKey,
-    ) -> Result<Score> {
+    ) -> Result<Score, ScoreDbError> {
         let dup_cursor_address_history = ReadTransaction::dup_cursor(txn, &AddressHistoryTable);
         let mut dup_cursor_block_snap = ReadTransaction::dup_cursor(txn, &BlockSnapshotTable);
         let block_entire_history: Vec<u64> = dup_cursor_address_history
@@ -547,9 +1256,14 @@ impl ScoreDb {
             .collect();
         let mut score_with_max_dirty_amount = Score::new_clean(U256::ZERO);
         for block_number in block_entire_history {
+            // The history entry guarantees a matching snapshot; a miss is
+            // on-disk corruption rather than an expected absence.
             let score = dup_cursor_block_snap
                 .set_subkey(&block_number, address)
-                .unwrap()
+                .ok_or_else(|| ScoreDbError::SnapshotMissing {
+                    block: block_number,
+                    address: address.to_string(),
+                })?
                 .value;
             if score.dirty_amount > score_with_max_dirty_amount.dirty_amount {
                 score_with_max_dirty_amount = score;
@@ -558,4 +1272,226 @@ impl ScoreDb {
 
         Ok(score_with_max_dirty_amount)
     }
+
+    /// Realized fiat inflow/outflow of tainted value for `address` over
+    /// `[from_block, to_block]`.
+    ///
+    /// Walks the address's [`AddressHistoryTable`] entries in order and, for
+    /// every change in `dirty_amount` between consecutive snapshots, values the
+    /// delta at that block's price ([`price_at`]). Dirty value entering the
+    /// address counts as positive and value leaving as negative, so the sum is
+    /// the net realized USD of tainted funds over the window. Changes before
+    /// `from_block` only establish the opening dirty balance; they are not
+    /// counted.
+    pub fn get_address_pnl(
+        &self,
+        txn: &MdbxReadTransaction,
+        address: &AddressKey,
+        from_block: u64,
+        to_block: u64,
+    ) -> Result<Decimal, ScoreDbError> {
+        let prices = self.price_index(txn);
+        let dup_cursor_address_history = ReadTransaction::dup_cursor(txn, &AddressHistoryTable);
+        let mut dup_cursor_block_snap = ReadTransaction::dup_cursor(txn, &BlockSnapshotTable);
+        let block_entire_history: Vec<u64> = dup_cursor_address_history
+            .into_iter_dup_of(address)
+            .map(|(_, v)| v)
+            .collect();
+
+        let mut previous_dirty = U256::ZERO;
+        let mut pnl = Decimal::ZERO;
+        for block_number in block_entire_history {
+            if block_number > to_block {
+                break;
+            }
+            // The history entry guarantees a matching snapshot; a miss is
+            // on-disk corruption rather than an expected absence.
+            let dirty = dup_cursor_block_snap
+                .set_subkey(&block_number, address)
+                .ok_or_else(|| ScoreDbError::SnapshotMissing {
+                    block: block_number,
+                    address: address.to_string(),
+                })?
+                .value
+                .dirty_amount;
+            // Blocks before the window only set the opening dirty balance.
+            if block_number >= from_block {
+                let price = price_at(&prices, block_number).unwrap_or(U256::ZERO);
+                if dirty >= previous_dirty {
+                    pnl += fiat_value(&(dirty - previous_dirty), &price)?;
+                } else {
+                    pnl -= fiat_value(&(previous_dirty - dirty), &price)?;
+                }
+            }
+            previous_dirty = dirty;
+        }
+
+        Ok(pnl)
+    }
+}
+
+/// Debits `transfer` from `sender`, returning the sender's residual score.
+///
+/// The residual dirty amount is clamped at zero so a policy that attributes
+/// more taint to the transfer than the sender itself carried (e.g. poison on a
+/// partially-dirty account) cannot underflow `U256::sub`. A transfer larger
+/// than the balance is a genuine accounting divergence and surfaces as
+/// [`ScoreDbError::BalanceUnderflow`].
+fn debit(sender: Score, transfer: &Score) -> Result<Score, ScoreDbError> {
+    if transfer.balance > sender.balance {
+        return Err(ScoreDbError::BalanceUnderflow {
+            balance: sender.balance,
+            transfer: transfer.balance,
+        });
+    }
+    let balance = sender.balance - transfer.balance;
+    let dirty = sender.dirty_amount.saturating_sub(transfer.dirty_amount);
+    Ok(Score::new(balance, dirty)?)
+}
+
+/// Whether token balances held by `account` originate taint: the sanctioned
+/// entity addresses whose incoming transfers are dirty at the source. This is
+/// the native-ETH sink rule applied to tokens, keyed off the sanctioned-entity
+/// set rather than the TC deposit pools.
+fn is_token_sink(account: &Address) -> bool {
+    BYBIT_EXPLOITER_ADDRESS.iter().any(|sink| sink == account)
+}
+
+/// Converts a `U256` amount into the Postgres `NUMERIC` representation,
+/// surfacing a malformed value as [`ScoreDbError::Serialize`] instead of
+/// aborting a long export.
+fn decimal(value: &U256) -> Result<Decimal, ScoreDbError> {
+    Decimal::from_str_exact(&value.to_string()).map_err(|e| ScoreDbError::Serialize(e.to_string()))
+}
+
+/// Number of wei in one ETH, and the scale factor applied to a [`UsdPrice`].
+const WEI_PER_ETH: u64 = 1_000_000_000_000_000_000;
+
+/// The USD value of a wei `amount` at a `usd_per_eth_scaled` price: the amount
+/// is converted from wei to ETH and the price from its `10^18` scale, so the
+/// result is plain USD.
+fn fiat_value(amount: &U256, usd_per_eth_scaled: &U256) -> Result<Decimal, ScoreDbError> {
+    let scale = Decimal::from(WEI_PER_ETH);
+    let eth = decimal(amount)? / scale;
+    let price = decimal(usd_per_eth_scaled)? / scale;
+    Ok(eth * price)
+}
+
+/// The price effective at `block_number`: the most recent recorded price at or
+/// before it, or `None` if no earlier price exists.
+fn price_at(prices: &BTreeMap<u64, U256>, block_number: u64) -> Option<U256> {
+    prices.range(..=block_number).next_back().map(|(_, p)| *p)
+}
+
+/// Creates the shared address-interning table if it is not already present.
+async fn ensure_addresses_table(client: &PostgresClient) -> Result<(), ScoreDbError> {
+    client
+        .execute(
+            "
+            CREATE TABLE IF NOT EXISTS addresses (
+                address CHAR(42) PRIMARY KEY,
+                address_id BIGSERIAL UNIQUE
+            );",
+            &[],
+        )
+        .await?;
+    Ok(())
+}
+
+/// Resolves the stable `address_id` for `address`, interning it on first sight.
+///
+/// The `cache` memoises the mapping for the lifetime of an export so a repeated
+/// address costs no round trip. On a miss the address is inserted with
+/// `ON CONFLICT DO NOTHING RETURNING address_id`; when another run already
+/// interned it the insert returns no row and the id is read back with a
+/// `SELECT`.
+async fn intern_address(
+    client: &PostgresClient,
+    cache: &mut BTreeMap<Address, i64>,
+    address: Address,
+) -> Result<i64, ScoreDbError> {
+    if let Some(id) = cache.get(&address) {
+        return Ok(*id);
+    }
+    let address_string = address.to_string();
+    let inserted = client
+        .query_opt(
+            "
+            INSERT INTO addresses (address) VALUES ($1)
+            ON CONFLICT (address) DO NOTHING
+            RETURNING address_id;",
+            &[&address_string],
+        )
+        .await?;
+    let address_id: i64 = match inserted {
+        Some(row) => row.get(0),
+        None => client
+            .query_one(
+                "SELECT address_id FROM addresses WHERE address = $1;",
+                &[&address_string],
+            )
+            .await?
+            .get(0),
+    };
+    cache.insert(address, address_id);
+    Ok(address_id)
+}
+
+/// Upserts the per-block aggregate row gathered during a snapshot export.
+async fn write_block_summary(
+    client: &PostgresClient,
+    block_number: u64,
+    tainted_address_count: i32,
+    total_dirty_amount: &U256,
+) -> Result<(), ScoreDbError> {
+    client
+        .execute(
+            "
+            INSERT INTO blocks (block_number, tainted_address_count, total_dirty_amount)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (block_number) DO UPDATE
+                SET tainted_address_count = EXCLUDED.tainted_address_count,
+                    total_dirty_amount = EXCLUDED.total_dirty_amount;",
+            &[
+                &(block_number as i64),
+                &tainted_address_count,
+                &decimal(total_dirty_amount)?,
+            ],
+        )
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tokens_into_a_sanctioned_sink_originate_taint() {
+        // A clean token balance received by a sanctioned sink becomes fully
+        // dirty, so taint can originate without an on-chain balance seed.
+        let sink = BYBIT_EXPLOITER_ADDRESS[0];
+        assert!(is_token_sink(&sink));
+        let seeded = Score::new_clean(U256::from(100)).as_dirty();
+        assert!(seeded.is_dirty());
+        assert_eq!(seeded.dirty_amount, U256::from(100));
+
+        // Moving the now-dirty balance onward carries the taint forward, so a
+        // transfer routed through a token no longer escapes the score.
+        let onward = PolicyKind::Haircut
+            .split_transfer(U256::from(40), &seeded)
+            .unwrap();
+        assert_eq!(onward.dirty_amount, U256::from(40));
+    }
+
+    #[test]
+    fn debit_clamps_residual_dirty_at_zero() {
+        // Poison taints the whole transfer even though the sender is only
+        // partially dirty; debiting must clamp rather than underflow.
+        let sender = Score::new(U256::from(10), U256::from(3)).unwrap();
+        let transfer = Score::new_dirty(U256::from(5));
+        let residual = debit(sender, &transfer).unwrap();
+        assert_eq!(residual.balance, U256::from(5));
+        assert_eq!(residual.dirty_amount, U256::ZERO);
+    }
 }
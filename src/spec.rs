@@ -0,0 +1,55 @@
+use alloy::primitives::{Address, U256};
+use eyre::Result;
+use serde::Deserialize;
+use std::{fs::File, io::BufReader, path::Path};
+
+use crate::constant::{
+    END_BLOCK_NUMBER, INI_BLOCK_NUMBER_TC, POS_BLOCK_NUMBER, TC_ETH_ADDRESS,
+};
+
+/// A chain-spec-style description of a scan, analogous to the OpenEthereum
+/// chain spec (`networkID`, `blockReward`, `frontierCompatibilityModeLimit`).
+///
+/// Everything that used to be hardcoded in `constant.rs` — the sanctioned
+/// source addresses, the PoW/PoS switch block, the static PoW block reward, and
+/// the scan range — is defined here so the tool can be pointed at other
+/// exploits, reward schedules, or testnets without recompiling.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ChainSpec {
+    /// EIP-155 network id of the chain being scanned.
+    pub network_id: u64,
+    /// Addresses that are fully dirty from the start of the scan.
+    pub dirty_sources: Vec<Address>,
+    /// First block at which the static PoW block reward is zero.
+    pub pos_block_number: u64,
+    /// Static PoW block reward, in wei.
+    pub static_block_reward: U256,
+    /// First block of the scan range.
+    pub ini_block_number: u64,
+    /// Last block of the scan range.
+    pub end_block_number: u64,
+}
+
+impl ChainSpec {
+    /// Loads a spec from a JSON file on disk.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let file = File::open(path)?;
+        let spec = serde_json::from_reader(BufReader::new(file))?;
+        Ok(spec)
+    }
+
+    /// The built-in Ethereum-mainnet TC spec, matching the previous
+    /// hardcoded constants. Used when no spec file is supplied.
+    pub fn mainnet() -> Self {
+        let dirty_sources = TC_ETH_ADDRESS.to_vec();
+        Self {
+            network_id: 1,
+            dirty_sources,
+            pos_block_number: POS_BLOCK_NUMBER,
+            // 2 ETH static block reward.
+            static_block_reward: U256::from(2_000_000_000_000_000_000_u128),
+            ini_block_number: INI_BLOCK_NUMBER_TC,
+            end_block_number: END_BLOCK_NUMBER,
+        }
+    }
+}